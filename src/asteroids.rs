@@ -11,6 +11,7 @@ use bevy_rapier2d::dynamics::AdditionalMassProperties;
 use bevy_rapier2d::geometry::Collider;
 use bevy_rapier2d::prelude::{Ccd, GravityScale, RigidBody, Velocity};
 
+use crate::difficulty::Difficulty;
 use crate::game_state::GameState;
 use crate::player::Player;
 use crate::shared::*;
@@ -88,6 +89,11 @@ impl Asteroid {
     }
   }
 
+  // The largest distance of the asteroid's outline from its centre
+  pub fn radius(&self) -> f32 {
+    self.size_range.end
+  }
+
   fn shape(&self) -> Polygon {
     let mut points = Vec::with_capacity(self.sides as usize);
     let step = 2. * PI / (self.sides);
@@ -107,11 +113,12 @@ pub fn spawn_asteroid_wave(
   event: &WaveEvent,
   mut commands: &mut Commands,
   mut asteroid_spawned_event: EventWriter<AsteroidSpawnedEvent>,
+  difficulty: &Difficulty,
 ) {
   for _ in 0..event.asteroid_count {
     let category = Category::L;
     let spawn_point = random_game_world_point_away_from_player(event.player_position, 100.);
-    spawn_asteroid(&mut commands, category, spawn_point);
+    spawn_asteroid(&mut commands, category, spawn_point, difficulty.asteroid_speed());
     asteroid_spawned_event.send(AsteroidSpawnedEvent);
   }
 }
@@ -121,6 +128,7 @@ fn spawn_smaller_asteroids_event(
   mut commands: Commands,
   mut asteroid_spawned_event: EventWriter<AsteroidSpawnedEvent>,
   player_query: Query<&Transform, With<Player>>,
+  difficulty: Res<Difficulty>,
 ) {
   for event in asteroid_event.read() {
     if let Some(closest_smaller_category) = match event.category {
@@ -141,7 +149,12 @@ fn spawn_smaller_asteroids_event(
         } else {
           random_game_world_point_close_to_origin_without_player_collision(event.origin, 25., Vec3::ZERO, 0.)
         };
-        spawn_asteroid(&mut commands, closest_smaller_category, spawn_point);
+        spawn_asteroid(
+          &mut commands,
+          closest_smaller_category,
+          spawn_point,
+          difficulty.asteroid_speed(),
+        );
         asteroid_spawned_event.send(AsteroidSpawnedEvent);
       }
     }
@@ -172,7 +185,7 @@ fn next_wave_event(
 }
 
 // TODO: Improve collider to support shapes more accurately
-fn spawn_asteroid(commands: &mut Commands, category: Category, spawn_point: Vec3) {
+fn spawn_asteroid(commands: &mut Commands, category: Category, spawn_point: Vec3, speed_multiplier: f32) {
   let asteroid = match category {
     Category::XL => Asteroid::large(),
     Category::L => Asteroid::large(),
@@ -203,7 +216,7 @@ fn spawn_asteroid(commands: &mut Commands, category: Category, spawn_point: Vec3
       linvel: Vec2::new(
         random_f32_range(-MAX_SPEED, MAX_SPEED),
         random_f32_range(-MAX_SPEED, MAX_SPEED),
-      ),
+      ) * speed_multiplier,
       angvel: random_f32_range(-MAX_ROTATIONAL_SPEED, MAX_ROTATIONAL_SPEED),
     },
     asteroid_collision_groups(),
@@ -1,31 +1,36 @@
-use crate::game_world::WORLD_SIZE;
+use crate::camera::InGameCameras;
 use crate::player::Player;
+use crate::toroidal;
 use bevy::app::{App, Plugin, Startup};
 use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+const TILE_SIZE: f32 = 720.;
+const TILES_PER_SIDE: i32 = 3; // Per layer, tiles repeat around the camera so that they always cover the screen
 
 pub struct BackgroundStarsPlugin;
 
 impl Plugin for BackgroundStarsPlugin {
   fn build(&self, app: &mut App) {
-    app
-      .add_systems(Startup, spawn_stars_system)
-      .add_systems(Update, parallax_scrolling_system);
+    app.add_systems(Startup, spawn_stars_system).add_systems(
+      PostUpdate,
+      parallax_scrolling_system.before(TransformSystem::TransformPropagate),
+    );
   }
 }
 
 #[derive(Component)]
 struct Stars {
   layer: u8, // 0 for foreground, 1 for background
+  tile: IVec2,
 }
 
 fn spawn_stars_system(mut commands: Commands, asset_server: Res<AssetServer>) {
   let foreground = asset_server.load("sprites/background_stars_foreground.png");
   let background = asset_server.load("sprites/background_stars_background.png");
   for layer in 0..=1 {
-    for x in 0..=1 {
-      for y in 0..=1 {
-        let x = x as f32 * 720.0 - (WORLD_SIZE / 2.);
-        let y = y as f32 * 720.0 - (WORLD_SIZE / 2.);
+    for x in 0..TILES_PER_SIDE {
+      for y in 0..TILES_PER_SIDE {
         commands.spawn((
           SpriteBundle {
             texture: if layer == 0 {
@@ -33,31 +38,57 @@ fn spawn_stars_system(mut commands: Commands, asset_server: Res<AssetServer>) {
             } else {
               background.clone()
             },
-            transform: Transform::from_xyz(x, y, if layer == 0 { -950.0 } else { -951.0 }),
+            transform: Transform::from_xyz(0., 0., if layer == 0 { -950.0 } else { -951.0 }),
             ..default()
           },
           Name::new("Stars: L".to_string() + &layer.to_string()),
-          Stars { layer },
+          Stars {
+            layer,
+            tile: IVec2::new(x, y),
+          },
         ));
       }
     }
   }
 }
 
+// Moves the stars with the camera and scrolls them against the player's movement, using the shortest path so that
+// wrapping around the edges of the world doesn't make the stars jump
 fn parallax_scrolling_system(
-  mut query: Query<(&Stars, &mut Transform), Without<Player>>,
+  mut query: Query<(&Stars, &mut Transform), (Without<Player>, Without<InGameCameras>)>,
   player_query: Query<&Transform, (With<Player>, Without<Stars>)>,
+  camera_query: Query<&Transform, (With<InGameCameras>, Without<Stars>)>,
   mut last_player_position: Local<Option<Vec3>>,
+  mut last_camera_position: Local<Option<Vec3>>,
+  mut scroll: Local<[Vec2; 2]>,
 ) {
-  if let Ok(player_transform) = player_query.get_single() {
-    if let Some(last_position) = *last_player_position {
-      let player_movement = player_transform.translation - last_position;
-      for (star, mut transform) in query.iter_mut() {
-        let speed = if star.layer == 0 { 0.5 } else { 0.2 };
-        transform.translation.x -= player_movement.x * speed;
-        transform.translation.y -= player_movement.y * speed;
-      }
+  let Some(camera_transform) = camera_query.iter().next() else {
+    return;
+  };
+  let camera_position = camera_transform.translation;
+  let camera_movement = last_camera_position
+    .map(|last_position| toroidal::shortest_delta(last_position, camera_position).truncate())
+    .unwrap_or_default();
+  *last_camera_position = Some(camera_position);
+
+  let player_movement = match (player_query.get_single(), *last_player_position) {
+    (Ok(player_transform), Some(last_position)) => {
+      toroidal::shortest_delta(last_position, player_transform.translation).truncate()
     }
-    *last_player_position = Some(player_transform.translation);
+    _ => Vec2::ZERO,
+  };
+  *last_player_position = player_query.get_single().ok().map(|transform| transform.translation);
+
+  for (layer, scroll) in scroll.iter_mut().enumerate() {
+    let speed = if layer == 0 { 0.5 } else { 0.2 };
+    *scroll -= camera_movement + player_movement * speed;
+  }
+
+  let span = TILE_SIZE * TILES_PER_SIDE as f32;
+  for (star, mut transform) in query.iter_mut() {
+    let offset = star.tile.as_vec2() * TILE_SIZE + scroll[star.layer as usize];
+    let offset = (offset + span / 2.).rem_euclid(Vec2::splat(span)) - span / 2.;
+    transform.translation.x = camera_position.x + offset.x;
+    transform.translation.y = camera_position.y + offset.y;
   }
 }
@@ -9,8 +9,8 @@ use bevy::render::view::RenderLayers;
 use bevy::window::WindowResized;
 
 use crate::game_state::GameState;
-use crate::game_world::WORLD_SIZE;
 use crate::player::Player;
+use crate::toroidal;
 
 pub const HIGH_RES_LAYER: RenderLayers = RenderLayers::layer(2);
 pub const PIXEL_PERFECT_BLOOM_LAYER: RenderLayers = RenderLayers::layer(1);
@@ -33,7 +33,7 @@ impl Plugin for PixelPerfectCameraPlugin {
 struct Canvas;
 
 #[derive(Component)]
-struct InGameCameras; // Cameras rendering on `PIXEL_PERFECT_LAYER`
+pub(crate) struct InGameCameras; // Cameras rendering on `PIXEL_PERFECT_LAYER`
 
 #[derive(Component)]
 struct OuterCamera; // Camera rendering `HIGH_RES_LAYER`
@@ -121,16 +121,11 @@ fn follow_player_system(
     return;
   };
 
+  // Follows the player across the edges of the world, wrapping around together with it
   for mut camera in cameras.iter_mut() {
-    let Vec3 { x, y, .. } = player.translation;
-    let direction = Vec3::new(x, y, camera.translation.z);
-
-    camera.translation = camera
-      .translation
-      .lerp(direction, time.delta_seconds() * CAM_LERP_FACTOR)
-      .clamp(
-        Vec3::new(-WORLD_SIZE / 5.5, -WORLD_SIZE / 3.1, camera.translation.z),
-        Vec3::new(WORLD_SIZE / 5.5, WORLD_SIZE / 3.1, camera.translation.z),
-      );
+    let delta = toroidal::shortest_delta(camera.translation, player.translation).truncate();
+    let target = camera.translation + delta.extend(0.);
+    let position = camera.translation.lerp(target, time.delta_seconds() * CAM_LERP_FACTOR);
+    camera.translation = toroidal::wrap_position(position);
   }
 }
@@ -2,10 +2,13 @@ use crate::asteroids::Asteroid;
 use crate::enemies::Enemy;
 use crate::game_state::GameState;
 use crate::player::Player;
+use crate::projectile;
 use crate::projectile::Projectile;
-use crate::shared::{CollisionDamage, CollisionEntityType, EntityType, ImpactInfo, PowerUp, Shield};
+use crate::shared::{CollisionDamage, CollisionEntityType, EntityType, ImpactInfo, PowerUp, Shield, TimedEffect};
+use crate::shared_events::{AreaDamageEvent, BeamDamageEvent, EnemyDamageEvent, PowerUpCollectedEvent};
 use crate::shared_events::{AsteroidDestroyedEvent, ExplosionEvent, ScoreEvent, ShieldDamageEvent};
-use crate::shared_events::{EnemyDamageEvent, PowerUpCollectedEvent};
+use crate::timed_power_ups::TimedEffects;
+use crate::toroidal;
 use bevy::app::{App, Plugin};
 use bevy::audio::Volume;
 use bevy::prelude::*;
@@ -15,7 +18,10 @@ pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
   fn build(&self, app: &mut App) {
-    app.add_systems(FixedUpdate, collision_system.run_if(in_state(GameState::Playing)));
+    app.add_systems(
+      FixedUpdate,
+      (collision_system, area_damage_event, beam_damage_event).run_if(in_state(GameState::Playing)),
+    );
   }
 }
 
@@ -26,6 +32,7 @@ struct CollisionEntityInfo {
   cet: CollisionEntityType,
   impact_info: Option<ImpactInfo>,
   other_cet: CollisionEntityType,
+  other_translation: Vec3,
   damage_dealt: u16,
 }
 
@@ -46,6 +53,7 @@ fn collision_system(
   mut enemy_damage_event: EventWriter<EnemyDamageEvent>,
   mut power_up_collected_event: EventWriter<PowerUpCollectedEvent>,
   mut shield_damage_event: EventWriter<ShieldDamageEvent>,
+  timed_effects: Res<TimedEffects>,
 ) {
   for collision_event in collision_events.read() {
     if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
@@ -68,11 +76,175 @@ fn collision_system(
         &mut enemy_damage_event,
         &mut power_up_collected_event,
         &mut shield_damage_event,
+        timed_effects.is_active(TimedEffect::Invulnerability),
       );
     }
   }
 }
 
+// Treats everything within the radius of an area damage event as if it collided with the source of the damage
+fn area_damage_event(
+  mut commands: Commands,
+  mut area_damage_events: EventReader<AreaDamageEvent>,
+  asset_server: Res<AssetServer>,
+  asteroid_query: Query<(Entity, &Transform, &ImpactInfo, &Asteroid), With<Asteroid>>,
+  player_query: Query<(Entity, &Transform, &ImpactInfo), With<Player>>,
+  enemy_query: Query<(Entity, &Transform, &ImpactInfo, &CollisionDamage), With<Enemy>>,
+  shield_query: Query<(Entity, &ImpactInfo), With<Shield>>,
+  mut asteroid_destroyed_event: EventWriter<AsteroidDestroyedEvent>,
+  mut explosion_event: EventWriter<ExplosionEvent>,
+  mut score_event: EventWriter<ScoreEvent>,
+  mut enemy_damage_event: EventWriter<EnemyDamageEvent>,
+  mut power_up_collected_event: EventWriter<PowerUpCollectedEvent>,
+  mut shield_damage_event: EventWriter<ShieldDamageEvent>,
+  timed_effects: Res<TimedEffects>,
+) {
+  for event in area_damage_events.read() {
+    let is_in_range = |transform: &Transform| toroidal::distance(event.origin, transform.translation) <= event.radius;
+    let other_cet = match event.by {
+      EntityType::Player => CollisionEntityType::Projectile(Projectile {
+        damage: event.damage,
+        life_time: 0.,
+        max_life_time: 0.,
+        pierce: 0,
+        ricochet: 0,
+      }),
+      EntityType::Mine => CollisionEntityType::Mine,
+      _ => CollisionEntityType::Enemy(CollisionDamage::new(event.damage)),
+    };
+    let entity_info =
+      |entity: Entity, transform: &Transform, cet: CollisionEntityType, impact_info: &ImpactInfo| CollisionEntityInfo {
+        entity,
+        transform: transform.clone(),
+        cet,
+        impact_info: Some(impact_info.clone()),
+        other_cet: other_cet.clone(),
+        other_translation: event.origin,
+        damage_dealt: event.damage,
+      };
+
+    let mut entity_list = vec![];
+    for (entity, transform, impact_info, asteroid) in asteroid_query.iter() {
+      if is_in_range(transform) {
+        entity_list.push(entity_info(
+          entity,
+          transform,
+          CollisionEntityType::Asteroid(asteroid.clone()),
+          impact_info,
+        ));
+      }
+    }
+    match event.by {
+      EntityType::Player => {
+        for (entity, transform, impact_info, collision_damage) in enemy_query.iter() {
+          if is_in_range(transform) {
+            entity_list.push(entity_info(
+              entity,
+              transform,
+              CollisionEntityType::Enemy(collision_damage.clone()),
+              impact_info,
+            ));
+          }
+        }
+      }
+      _ => {
+        if let Ok((entity, transform, impact_info)) = player_query.get_single() {
+          if is_in_range(transform) {
+            entity_list.push(match shield_query.get_single() {
+              Ok((shield, shield_impact_info)) => {
+                entity_info(shield, transform, CollisionEntityType::Shield, shield_impact_info)
+              }
+              Err(_) => entity_info(entity, transform, CollisionEntityType::Player, impact_info),
+            });
+          }
+        }
+      }
+    }
+
+    debug!(
+      "Area damage at {:?} affected {} entities",
+      event.origin,
+      entity_list.len()
+    );
+    handle_collisions(
+      &mut commands,
+      &asset_server,
+      entity_list,
+      &mut explosion_event,
+      &mut asteroid_destroyed_event,
+      &mut score_event,
+      &mut enemy_damage_event,
+      &mut power_up_collected_event,
+      &mut shield_damage_event,
+      timed_effects.is_active(TimedEffect::Invulnerability),
+    );
+  }
+}
+
+// Treats the target of the laser beam as if it collided with a projectile dealing the beam's damage
+fn beam_damage_event(
+  mut commands: Commands,
+  mut beam_damage_events: EventReader<BeamDamageEvent>,
+  asset_server: Res<AssetServer>,
+  asteroid_query: Query<(Entity, &Transform, &ImpactInfo, &Asteroid), With<Asteroid>>,
+  enemy_query: Query<(Entity, &Transform, &ImpactInfo, &CollisionDamage), With<Enemy>>,
+  mut asteroid_destroyed_event: EventWriter<AsteroidDestroyedEvent>,
+  mut explosion_event: EventWriter<ExplosionEvent>,
+  mut score_event: EventWriter<ScoreEvent>,
+  mut enemy_damage_event: EventWriter<EnemyDamageEvent>,
+  mut power_up_collected_event: EventWriter<PowerUpCollectedEvent>,
+  mut shield_damage_event: EventWriter<ShieldDamageEvent>,
+  timed_effects: Res<TimedEffects>,
+) {
+  for event in beam_damage_events.read() {
+    let (entity, transform, impact_info, cet) =
+      if let Ok((entity, transform, impact_info, asteroid)) = asteroid_query.get(event.target) {
+        (
+          entity,
+          transform,
+          impact_info,
+          CollisionEntityType::Asteroid(asteroid.clone()),
+        )
+      } else if let Ok((entity, transform, impact_info, collision_damage)) = enemy_query.get(event.target) {
+        (
+          entity,
+          transform,
+          impact_info,
+          CollisionEntityType::Enemy(collision_damage.clone()),
+        )
+      } else {
+        continue;
+      };
+    let entity_info = CollisionEntityInfo {
+      entity,
+      transform: transform.clone(),
+      cet,
+      impact_info: Some(impact_info.clone()),
+      other_cet: CollisionEntityType::Projectile(Projectile {
+        damage: event.damage,
+        life_time: 0.,
+        max_life_time: 0.,
+        pierce: 0,
+        ricochet: 0,
+      }),
+      other_translation: transform.translation,
+      damage_dealt: event.damage,
+    };
+    handle_collisions(
+      &mut commands,
+      &asset_server,
+      vec![entity_info],
+      &mut explosion_event,
+      &mut asteroid_destroyed_event,
+      &mut score_event,
+      &mut enemy_damage_event,
+      &mut power_up_collected_event,
+      &mut shield_damage_event,
+      timed_effects.is_active(TimedEffect::Invulnerability),
+    );
+  }
+}
+
 fn get_collision_entity_info(
   colliding_entities: [&Entity; 2],
   asteroid_query: &Query<(Entity, &Transform, &ImpactInfo, &Asteroid), With<Asteroid>>,
@@ -92,6 +264,7 @@ fn get_collision_entity_info(
         cet: CollisionEntityType::Asteroid(asteroid.clone()),
         impact_info: Some(impact_info.clone()),
         other_cet: CollisionEntityType::Unknown,
+        other_translation: Vec3::ZERO,
         damage_dealt: 0,
       });
     } else if let Ok((entity, transform, impact_info, projectile)) = projectile_query.get(*collision_entity) {
@@ -101,6 +274,7 @@ fn get_collision_entity_info(
         cet: CollisionEntityType::Projectile(projectile.clone()),
         impact_info: Some(impact_info.clone()),
         other_cet: CollisionEntityType::Unknown,
+        other_translation: Vec3::ZERO,
         damage_dealt: 0,
       });
     } else if let Ok((entity, transform, impact_info, collision_dmg)) = enemy_query.get(*collision_entity) {
@@ -110,6 +284,7 @@ fn get_collision_entity_info(
         cet: CollisionEntityType::Enemy(collision_dmg.clone()),
         impact_info: Some(impact_info.clone()),
         other_cet: CollisionEntityType::Unknown,
+        other_translation: Vec3::ZERO,
         damage_dealt: 0,
       });
     } else if let Ok((entity, transform, impact_info)) = shield_query.get(*collision_entity) {
@@ -119,6 +294,7 @@ fn get_collision_entity_info(
         cet: CollisionEntityType::Shield,
         impact_info: Some(impact_info.clone()),
         other_cet: CollisionEntityType::Unknown,
+        other_translation: Vec3::ZERO,
         damage_dealt: 0,
       });
     } else if let Ok((entity, transform, impact_info)) = player_query.get(*collision_entity) {
@@ -128,6 +304,7 @@ fn get_collision_entity_info(
         cet: CollisionEntityType::Player,
         impact_info: Some(impact_info.clone()),
         other_cet: CollisionEntityType::Unknown,
+        other_translation: Vec3::ZERO,
         damage_dealt: 0,
       });
     } else if let Ok((entity, transform, impact_info, power_up)) = power_up_query.get(*collision_entity) {
@@ -137,6 +314,7 @@ fn get_collision_entity_info(
         cet: CollisionEntityType::PowerUp(power_up.clone()),
         impact_info: Some(impact_info.clone()),
         other_cet: CollisionEntityType::Unknown,
+        other_translation: Vec3::ZERO,
         damage_dealt: 0,
       });
     }
@@ -148,22 +326,23 @@ fn get_collision_entity_info(
     entity_list.len()
   );
 
-  // Determine the "other" entity type for each entity
-  let other_entity_types: Vec<_> = entity_list
+  // Determine the "other" entity type and position for each entity
+  let other_entities: Vec<_> = entity_list
     .iter()
     .map(|entity_info| {
       entity_list
         .iter()
         .find(|other_entity_info| other_entity_info.entity != entity_info.entity)
-        .map(|other_entity_info| other_entity_info.cet.clone())
-        .unwrap_or(CollisionEntityType::Unknown)
+        .map(|other_entity_info| (other_entity_info.cet.clone(), other_entity_info.transform.translation))
+        .unwrap_or((CollisionEntityType::Unknown, Vec3::ZERO))
     })
     .collect();
 
   // Update the damage and other entity type for each entity so that each collision info contains
   // the type of the other entity it collided with and the damage dealt by it
-  for (entity_info, other_entity_type) in entity_list.iter_mut().zip(other_entity_types) {
+  for (entity_info, (other_entity_type, other_translation)) in entity_list.iter_mut().zip(other_entities) {
     entity_info.other_cet = other_entity_type.clone();
+    entity_info.other_translation = other_translation;
     entity_info.damage_dealt = match other_entity_type {
       CollisionEntityType::Projectile(projectile) => projectile.damage,
       CollisionEntityType::Enemy(collision_damage) => collision_damage.damage,
@@ -184,6 +363,7 @@ fn handle_collisions(
   enemy_damage_event: &mut EventWriter<EnemyDamageEvent>,
   power_up_event: &mut EventWriter<PowerUpCollectedEvent>,
   shield_damage_event: &mut EventWriter<ShieldDamageEvent>,
+  is_invulnerable: bool,
 ) {
   for entity_info in entity_list {
     match entity_info.cet {
@@ -196,12 +376,14 @@ fn handle_collisions(
       ),
       CollisionEntityType::Projectile(_) => projectile_collision(entity_info, commands, explosion_event),
       CollisionEntityType::Enemy(_) => enemy_collision(entity_info, explosion_event, enemy_damage_event),
+      // An invulnerable player still destroys what they run into but takes no damage themselves
+      CollisionEntityType::Player | CollisionEntityType::Shield if is_invulnerable => {}
       CollisionEntityType::Player => {
         player_collision(entity_info, commands, asset_server, explosion_event, score_event)
       }
       CollisionEntityType::PowerUp(_) => power_up_collision(entity_info, commands, explosion_event, power_up_event),
       CollisionEntityType::Shield => shield_collision(entity_info, shield_damage_event),
-      CollisionEntityType::Unknown => log_error(&entity_info, "handle_collisions"),
+      CollisionEntityType::Mine | CollisionEntityType::Unknown => log_error(&entity_info, "handle_collisions"),
     }
   }
 }
@@ -219,7 +401,10 @@ fn asteroid_collision(
       origin: entity_info.transform.translation,
     });
     send_explosion_event_from_entity_info(&entity_info, explosion_event);
-    score_event.send(ScoreEvent { score: asteroid.score });
+    // Asteroids caught in the blast of a mine don't count towards the player's score
+    if !matches!(entity_info.other_cet, CollisionEntityType::Mine) {
+      score_event.send(ScoreEvent { score: asteroid.score });
+    }
     commands.entity(entity_info.entity).despawn();
   } else {
     log_error(&entity_info, "asteroid_collision");
@@ -260,8 +445,18 @@ fn projectile_collision(
   commands: &mut Commands,
   explosion_event: &mut EventWriter<ExplosionEvent>,
 ) {
-  if matches!(entity_info.cet, CollisionEntityType::Projectile(_)) {
-    commands.entity(entity_info.entity).despawn();
+  if let CollisionEntityType::Projectile(projectile) = &entity_info.cet {
+    let is_asteroid = matches!(entity_info.other_cet, CollisionEntityType::Asteroid(_));
+    if is_asteroid && projectile.ricochet > 0 {
+      let normal = toroidal::direction(entity_info.other_translation, entity_info.transform.translation).truncate();
+      commands
+        .entity(entity_info.entity)
+        .add(move |entity: EntityWorldMut| projectile::ricochet(entity, normal));
+    } else if projectile.pierce > 0 {
+      commands.entity(entity_info.entity).add(projectile::pierce);
+    } else {
+      commands.entity(entity_info.entity).despawn();
+    }
     send_explosion_event_from_entity_info(&entity_info, explosion_event);
   } else {
     log_error(&entity_info, "projectile_collision");
@@ -0,0 +1,214 @@
+use crate::enemies::scaling::EnemyScaling;
+use crate::game_state::GameState;
+use crate::shared_resources::{Score, Wave};
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+use std::fmt;
+
+const MAX_HIGH_SCORES: usize = 10;
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .register_type::<Difficulty>()
+      .register_type::<HighScores>()
+      .init_resource::<Difficulty>()
+      .init_resource::<HighScores>()
+      .add_systems(Update, select_difficulty_system.run_if(in_state(GameState::Starting)))
+      .add_systems(OnExit(GameState::Starting), apply_difficulty_system)
+      .add_systems(OnEnter(GameState::Dead), record_high_score_system);
+  }
+}
+
+// The preset chosen before a game starts - all multipliers are relative to `Normal`
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Default)]
+#[reflect(Resource)]
+pub(crate) enum Difficulty {
+  Easy,
+  #[default]
+  Normal,
+  Hard,
+  Nightmare,
+}
+
+impl Difficulty {
+  pub const ALL: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Normal,
+    Difficulty::Hard,
+    Difficulty::Nightmare,
+  ];
+
+  // Used as the difficulty of the enemy scaling curves i.e. mostly affects enemy health
+  pub fn enemy_strength(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 0.7,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 1.3,
+      Difficulty::Nightmare => 1.7,
+    }
+  }
+
+  pub fn enemy_damage(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 0.6,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 1.4,
+      Difficulty::Nightmare => 2.,
+    }
+  }
+
+  // Scales how well enemies lead their shots, see `calculate_lead_direction`
+  pub fn enemy_accuracy(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 0.5,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 1.3,
+      Difficulty::Nightmare => 1.6,
+    }
+  }
+
+  pub fn asteroid_speed(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 0.8,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 1.2,
+      Difficulty::Nightmare => 1.5,
+    }
+  }
+
+  pub fn shield_strength(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 1.5,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 0.8,
+      Difficulty::Nightmare => 0.6,
+    }
+  }
+
+  pub fn power_up_frequency(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 1.5,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 0.75,
+      Difficulty::Nightmare => 0.5,
+    }
+  }
+
+  pub fn score_multiplier(&self) -> f32 {
+    match self {
+      Difficulty::Easy => 0.5,
+      Difficulty::Normal => 1.,
+      Difficulty::Hard => 1.5,
+      Difficulty::Nightmare => 2.5,
+    }
+  }
+
+  // Whether a power up that appears every `base_interval` waves on `Normal` is due in the given wave
+  pub fn is_power_up_due(&self, wave: u16, base_interval: f32) -> bool {
+    let interval = (base_interval / self.power_up_frequency()).round().max(1.) as u16;
+    (wave + 1) % interval == 0
+  }
+
+  fn key(&self) -> KeyCode {
+    match self {
+      Difficulty::Easy => KeyCode::Digit1,
+      Difficulty::Normal => KeyCode::Digit2,
+      Difficulty::Hard => KeyCode::Digit3,
+      Difficulty::Nightmare => KeyCode::Digit4,
+    }
+  }
+}
+
+impl fmt::Display for Difficulty {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{:?}", self)
+  }
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct HighScore {
+  pub score: u16,
+  pub wave: u16,
+  pub difficulty: Difficulty,
+}
+
+// The best scores of this session, highest first
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+  pub fn best(&self, difficulty: Difficulty) -> Option<&HighScore> {
+    self.0.iter().find(|high_score| high_score.difficulty == difficulty)
+  }
+}
+
+fn select_difficulty_system(keyboard_input: Res<ButtonInput<KeyCode>>, mut difficulty: ResMut<Difficulty>) {
+  if let Some(selected) = Difficulty::ALL.iter().find(|d| keyboard_input.just_pressed(d.key())) {
+    if *difficulty != *selected {
+      *difficulty = *selected;
+      info!("Difficulty set to {}", *selected);
+    }
+  }
+}
+
+fn apply_difficulty_system(difficulty: Res<Difficulty>, mut enemy_scaling: ResMut<EnemyScaling>) {
+  enemy_scaling.difficulty = difficulty.enemy_strength();
+}
+
+pub(crate) fn record_high_score_system(
+  score: Res<Score>,
+  wave: Res<Wave>,
+  difficulty: Res<Difficulty>,
+  mut high_scores: ResMut<HighScores>,
+) {
+  if score.0 == 0 {
+    return;
+  }
+  high_scores.0.push(HighScore {
+    score: score.0,
+    wave: wave.0,
+    difficulty: *difficulty,
+  });
+  high_scores.0.sort_by(|a, b| b.score.cmp(&a.score));
+  high_scores.0.truncate(MAX_HIGH_SCORES);
+  debug!("High scores: {:?}", high_scores.0);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn due_waves(difficulty: Difficulty, base_interval: f32) -> Vec<u16> {
+    (1..=12)
+      .filter(|wave| difficulty.is_power_up_due(*wave, base_interval))
+      .collect()
+  }
+
+  #[test]
+  fn power_up_is_due_every_base_interval_on_normal() {
+    assert_eq!(due_waves(Difficulty::Normal, 3.), vec![2, 5, 8, 11]);
+    assert_eq!(due_waves(Difficulty::Normal, 5.), vec![4, 9]);
+  }
+
+  #[test]
+  fn power_ups_are_due_more_often_on_easier_difficulties() {
+    assert_eq!(due_waves(Difficulty::Easy, 3.), vec![1, 3, 5, 7, 9, 11]);
+    assert_eq!(due_waves(Difficulty::Hard, 3.), vec![3, 7, 11]);
+    assert_eq!(due_waves(Difficulty::Nightmare, 3.), vec![5, 11]);
+  }
+
+  #[test]
+  fn power_up_interval_is_never_shorter_than_one_wave() {
+    assert_eq!(due_waves(Difficulty::Easy, 0.5), (1..=12).collect::<Vec<u16>>());
+  }
+
+  #[test]
+  fn interval_is_rounded_to_whole_waves() {
+    // 2 / 0.75 rounds to an interval of 3 waves
+    assert_eq!(due_waves(Difficulty::Hard, 2.), vec![2, 5, 8, 11]);
+  }
+}
@@ -0,0 +1,252 @@
+use crate::asteroids::Asteroid;
+use crate::enemies::Enemy;
+use crate::game_state::GameState;
+use crate::projectile::Projectile;
+use crate::shared::{is_friendly, EntityType};
+use crate::shared_events::{ProjectileSpawnEvent, ShieldDamageEvent};
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier2d::pipeline::CollisionEvent;
+use bevy_rapier2d::prelude::CollisionGroups;
+
+pub struct DifficultyDirectorPlugin;
+
+impl Plugin for DifficultyDirectorPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .register_type::<DifficultyDirector>()
+      .init_resource::<DifficultyDirector>()
+      .add_systems(OnEnter(GameState::Dead), player_death_system)
+      .add_systems(Update, track_performance_system.run_if(in_state(GameState::Playing)))
+      .add_systems(FixedUpdate, track_hits_system.run_if(in_state(GameState::Playing)));
+  }
+}
+
+// Tracks how well the player has been doing recently and nudges the difficulty of the next waves up or down within
+// bounds - the rating ranges from -1 (struggling) to 1 (dominating) and is retained across games
+#[derive(Resource, Debug, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct DifficultyDirector {
+  pub enabled: bool,
+  pub rating: f32,
+  pub responsiveness: f32,      // How much the latest wave affects the rating (0 to 1)
+  pub target_clear_time: f32,   // Seconds in which an average player clears a wave
+  pub target_accuracy: f32,     // Share of player shots that hit something for an average player
+  pub budget_adjustment: f32,   // Maximum relative change of a wave's threat budget
+  pub accuracy_adjustment: f32, // Maximum change of enemy accuracy
+  pub deaths: u16,
+  pub last_clear_time: f32,
+  pub last_accuracy: f32,
+  pub last_shield_damage: u16,
+  current: WaveStats,
+}
+
+#[derive(Debug, Default, Reflect)]
+struct WaveStats {
+  elapsed: f32,
+  shots: u32,
+  hits: u32,
+  shield_damage: u16,
+}
+
+impl Default for DifficultyDirector {
+  fn default() -> Self {
+    DifficultyDirector {
+      enabled: true,
+      rating: 0.,
+      responsiveness: 0.3,
+      target_clear_time: 60.,
+      target_accuracy: 0.35,
+      budget_adjustment: 0.25,
+      accuracy_adjustment: 0.15,
+      deaths: 0,
+      last_clear_time: 0.,
+      last_accuracy: 0.,
+      last_shield_damage: 0,
+      current: WaveStats::default(),
+    }
+  }
+}
+
+impl DifficultyDirector {
+  // The multiplier for the threat budget of the next wave
+  pub fn budget_multiplier(&self) -> f32 {
+    if self.enabled {
+      1. + self.rating * self.budget_adjustment
+    } else {
+      1.
+    }
+  }
+
+  // The amount by which enemy accuracy is raised or lowered
+  pub fn accuracy_modifier(&self) -> f32 {
+    if self.enabled {
+      self.rating * self.accuracy_adjustment
+    } else {
+      0.
+    }
+  }
+
+  fn record(&mut self, performance: f32) {
+    let responsiveness = self.responsiveness.clamp(0., 1.);
+    self.rating = (self.rating * (1. - responsiveness) + performance * responsiveness).clamp(-1., 1.);
+    self.current = WaveStats::default();
+    debug!("Difficulty director rating is now {:.2}", self.rating);
+  }
+
+  // Discards the stats of the current wave e.g. when starting a new game
+  pub fn reset_wave_stats(&mut self) {
+    self.current = WaveStats::default();
+  }
+
+  // Rates the wave that was just cleared from -1 to 1 based on clear time, accuracy and shield damage taken
+  pub fn evaluate_cleared_wave(&mut self) {
+    let stats = &self.current;
+    let time_score = ((self.target_clear_time - stats.elapsed) / self.target_clear_time).clamp(-1., 1.);
+    let accuracy = if stats.shots > 0 {
+      (stats.hits as f32 / stats.shots as f32).min(1.)
+    } else {
+      self.target_accuracy
+    };
+    let accuracy_score = ((accuracy - self.target_accuracy) / self.target_accuracy).clamp(-1., 1.);
+    let shield_score = -(stats.shield_damage as f32 / 15.).min(1.);
+    self.last_clear_time = stats.elapsed;
+    self.last_accuracy = accuracy;
+    self.last_shield_damage = stats.shield_damage;
+    self.record((time_score + accuracy_score + shield_score) / 3.);
+  }
+}
+
+fn track_performance_system(
+  time: Res<Time>,
+  mut director: ResMut<DifficultyDirector>,
+  mut projectile_spawn_events: EventReader<ProjectileSpawnEvent>,
+  mut shield_damage_events: EventReader<ShieldDamageEvent>,
+) {
+  let stats = &mut director.current;
+  stats.elapsed += time.delta_seconds();
+  stats.shots += projectile_spawn_events
+    .read()
+    .filter(|event| matches!(event.projectile_info.by, EntityType::Player))
+    .count() as u32;
+  stats.shield_damage += shield_damage_events.read().map(|event| event.damage).sum::<u16>();
+}
+
+// Counts the player's projectiles that made contact with an asteroid or enemy, each projectile at most once so that
+// piercing and ricocheting projectiles don't count as several hits - runs alongside the collision system so that
+// projectiles despawned on impact can still be identified
+fn track_hits_system(
+  mut director: ResMut<DifficultyDirector>,
+  mut collision_events: EventReader<CollisionEvent>,
+  projectile_query: Query<&CollisionGroups, With<Projectile>>,
+  asteroid_query: Query<(), With<Asteroid>>,
+  enemy_query: Query<(), With<Enemy>>,
+  mut hit_projectiles: Local<HashSet<Entity>>,
+) {
+  hit_projectiles.retain(|entity| projectile_query.contains(*entity));
+  for collision_event in collision_events.read() {
+    if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
+      for (projectile, target) in [(*entity1, *entity2), (*entity2, *entity1)] {
+        let is_player_projectile = projectile_query.get(projectile).is_ok_and(is_friendly);
+        let is_target = asteroid_query.contains(target) || enemy_query.contains(target);
+        if is_player_projectile && is_target && hit_projectiles.insert(projectile) {
+          director.current.hits += 1;
+        }
+      }
+    }
+  }
+}
+
+fn player_death_system(mut director: ResMut<DifficultyDirector>) {
+  director.deaths += 1;
+  director.record(-1.);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::assert_approx_eq;
+
+  fn director_after_wave(elapsed: f32, shots: u32, hits: u32, shield_damage: u16) -> DifficultyDirector {
+    let mut director = DifficultyDirector {
+      current: WaveStats {
+        elapsed,
+        shots,
+        hits,
+        shield_damage,
+      },
+      ..default()
+    };
+    director.evaluate_cleared_wave();
+    director
+  }
+
+  #[test]
+  fn average_wave_leaves_the_rating_unchanged() {
+    let director = director_after_wave(60., 100, 35, 0);
+    assert_approx_eq(director.rating, 0.);
+    assert_approx_eq(director.budget_multiplier(), 1.);
+    assert_approx_eq(director.accuracy_modifier(), 0.);
+  }
+
+  #[test]
+  fn dominated_wave_raises_the_rating() {
+    let director = director_after_wave(0., 100, 100, 0);
+    assert_approx_eq(director.rating, 0.3 * 2. / 3.);
+    assert!(director.budget_multiplier() > 1.);
+    assert!(director.accuracy_modifier() > 0.);
+  }
+
+  #[test]
+  fn struggled_wave_lowers_the_rating_as_far_as_possible() {
+    let director = director_after_wave(180., 100, 0, 30);
+    assert_approx_eq(director.rating, -0.3);
+    assert!(director.budget_multiplier() < 1.);
+    assert!(director.accuracy_modifier() < 0.);
+  }
+
+  #[test]
+  fn wave_without_shots_counts_as_target_accuracy() {
+    let director = director_after_wave(60., 0, 0, 0);
+    assert_approx_eq(director.last_accuracy, director.target_accuracy);
+    assert_approx_eq(director.rating, 0.);
+  }
+
+  #[test]
+  fn evaluation_records_and_resets_the_wave_stats() {
+    let director = director_after_wave(42., 10, 5, 7);
+    assert_approx_eq(director.last_clear_time, 42.);
+    assert_approx_eq(director.last_accuracy, 0.5);
+    assert_eq!(director.last_shield_damage, 7);
+    assert_eq!(director.current.shots, 0);
+    assert_eq!(director.current.elapsed, 0.);
+  }
+
+  #[test]
+  fn responsiveness_and_rating_are_clamped() {
+    let mut director = DifficultyDirector {
+      responsiveness: 5.,
+      ..default()
+    };
+    for _ in 0..10 {
+      director.current = WaveStats {
+        elapsed: 0.,
+        shots: 10,
+        hits: 20,
+        shield_damage: 0,
+      };
+      director.evaluate_cleared_wave();
+      assert!(director.rating <= 1.);
+    }
+    assert_approx_eq(director.rating, 2. / 3.);
+  }
+
+  #[test]
+  fn disabled_director_does_not_adjust_anything() {
+    let mut director = director_after_wave(0., 100, 100, 0);
+    director.enabled = false;
+    assert_approx_eq(director.budget_multiplier(), 1.);
+    assert_approx_eq(director.accuracy_modifier(), 0.);
+  }
+}
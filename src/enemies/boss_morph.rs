@@ -1,3 +1,5 @@
+use crate::enemies::steering::{Approach, SpatialIndex, Steering};
+use crate::enemies::warp_in::{spawn_warp_in, WarpInEnemy};
 use crate::enemies::{move_toward_target, Enemy};
 use crate::game_state::GameState;
 use crate::player::Player;
@@ -6,6 +8,8 @@ use crate::shared::{
   Substance, WrapAroundEntity,
 };
 use crate::shared_events::WaveEvent;
+use crate::timed_power_ups::WorldTime;
+use crate::toroidal;
 use bevy::app::{App, FixedUpdate, Plugin};
 use bevy::asset::AssetServer;
 use bevy::audio::Volume;
@@ -100,22 +104,16 @@ enum Behaviour {
 #[derive(Component, Deref, DerefMut, Clone)]
 struct AnimationTimer(Timer);
 
-pub fn spawn_once(
-  event: &WaveEvent,
-  mut commands: &mut Commands,
-  asset_server: &Res<AssetServer>,
-  texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
-) {
+pub fn spawn_once(event: &WaveEvent, commands: &mut Commands, asset_server: &Res<AssetServer>) {
   if !event.morph_boss {
     return;
   }
   let spawn_point = random_game_world_point_away_from_player(event.player_position, 300.);
-  spawn_morph_boss(&mut commands, &asset_server, spawn_point, texture_atlas_layouts);
-  info!("Spawn: Morph boss at {:?}", spawn_point);
+  spawn_warp_in(commands, asset_server, spawn_point, WarpInEnemy::MorphBoss);
 }
 
-fn spawn_morph_boss(
-  commands: &mut &mut Commands,
+pub(crate) fn spawn_morph_boss(
+  commands: &mut Commands,
   asset_server: &Res<AssetServer>,
   spawn_point: Vec3,
   texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
@@ -168,12 +166,16 @@ fn spawn_morph_boss(
       MorphBoss::new(),
     ))
     .insert(default_enemy_collision_groups())
-    .insert(CollisionDamage::new(15));
+    .insert(CollisionDamage::new(15))
+    .insert(Steering {
+      radius: 25.,
+      approach: Approach::Seek,
+    });
 }
 
-fn animate_sprite_system(time: Res<Time>, mut query: Query<(&mut MorphBoss, &mut TextureAtlas)>) {
+fn animate_sprite_system(world_time: Res<WorldTime>, mut query: Query<(&mut MorphBoss, &mut TextureAtlas)>) {
   for (mut morph_boss, mut atlas) in &mut query {
-    morph_boss.current_state.timer.tick(time.delta());
+    morph_boss.current_state.timer.tick(world_time.delta());
     if morph_boss.current_state.timer.just_finished() {
       atlas.index = if atlas.index >= morph_boss.current_state.last {
         morph_boss.current_state.first
@@ -197,24 +199,39 @@ fn boss_movement_system(
       &mut MorphBoss,
       &TextureAtlas,
       &SpatialAudioSink,
+      &Steering,
     ),
     Without<Player>,
   >,
   player_query: Query<&Transform, With<Player>>,
-  time: Res<Time>,
+  spatial_index: Res<SpatialIndex>,
+  world_time: Res<WorldTime>,
   asset_server: Res<AssetServer>,
   mut commands: Commands,
 ) {
-  for (entity, mut transform, mut velocity, enemy, mut morph_boss, atlas, audio_sink) in boss_query.iter_mut() {
+  for (entity, mut transform, mut velocity, enemy, mut morph_boss, atlas, audio_sink, steering) in boss_query.iter_mut()
+  {
     match morph_boss.current_state.behaviour {
-      Behaviour::Idle => idle_state(&player_query, &mut transform, &mut velocity, enemy, &mut morph_boss),
+      Behaviour::Idle => idle_state(
+        entity,
+        &player_query,
+        &mut transform,
+        &mut velocity,
+        enemy,
+        &mut morph_boss,
+        steering,
+        &spatial_index,
+      ),
       Behaviour::Rotate => rotate_state(
+        entity,
         &player_query,
         &mut transform,
         &mut velocity,
         enemy,
         &mut morph_boss,
         audio_sink,
+        steering,
+        &spatial_index,
       ),
       Behaviour::Morph => morph_state(
         &entity,
@@ -228,7 +245,7 @@ fn boss_movement_system(
       ),
       Behaviour::Attack => attack_state(
         &player_query,
-        &time,
+        &world_time,
         &mut transform,
         &mut velocity,
         enemy,
@@ -240,18 +257,29 @@ fn boss_movement_system(
 }
 
 fn idle_state(
+  entity: Entity,
   player_query: &Query<&Transform, With<Player>>,
   transform: &mut Mut<Transform>,
   velocity: &mut Mut<Velocity>,
   enemy: &Enemy,
   morph_boss: &mut Mut<MorphBoss>,
+  steering: &Steering,
+  spatial_index: &SpatialIndex,
 ) {
   if let Ok(player) = player_query.get_single().as_ref() {
     // State behaviour
-    move_toward_target(player, &transform, &mut *velocity, enemy.movement_speed);
+    move_toward_target(
+      entity,
+      player,
+      &transform,
+      &mut *velocity,
+      enemy.movement_speed,
+      steering,
+      spatial_index,
+    );
 
     // Exit condition
-    if (transform.translation - player.translation).length() < ROTATING_THRESHOLD {
+    if toroidal::distance(transform.translation, player.translation) < ROTATING_THRESHOLD {
       morph_boss.current_state = State::rotate();
       velocity.angvel = 0.;
       debug!("Morph boss: Rotate state");
@@ -260,16 +288,27 @@ fn idle_state(
 }
 
 fn rotate_state(
+  entity: Entity,
   player_query: &Query<&Transform, With<Player>>,
   mut transform: &mut Mut<Transform>,
   velocity: &mut Mut<Velocity>,
   enemy: &Enemy,
   morph_boss: &mut Mut<MorphBoss>,
   audio_sink: &SpatialAudioSink,
+  steering: &Steering,
+  spatial_index: &SpatialIndex,
 ) {
   if let Ok(player) = player_query.get_single().as_ref() {
     // State behaviour
-    move_toward_target(player, &transform, &mut *velocity, enemy.movement_speed);
+    move_toward_target(
+      entity,
+      player,
+      &transform,
+      &mut *velocity,
+      enemy.movement_speed,
+      steering,
+      spatial_index,
+    );
     let difference = rotate_towards_target(player, &mut transform);
 
     // Exit condition
@@ -333,7 +372,7 @@ fn morph_state(
 
 fn attack_state(
   player_query: &Query<&Transform, With<Player>>,
-  time: &Res<Time>,
+  world_time: &Res<WorldTime>,
   transform: &mut Mut<Transform>,
   velocity: &mut Mut<Velocity>,
   enemy: &Enemy,
@@ -343,10 +382,10 @@ fn attack_state(
     // State behaviour
     let direction = transform.rotation * Vec3::X;
     let acceleration = Vec2::new(direction.x, direction.y) * enemy.movement_speed * ATTACK_MOVEMENT_MULTIPLIER;
-    velocity.linvel += acceleration * time.delta_seconds();
+    velocity.linvel += acceleration * world_time.delta_seconds();
 
     // Exit condition
-    if (player.translation - transform.translation).length() > REVERTING_THRESHOLD {
+    if toroidal::distance(transform.translation, player.translation) > REVERTING_THRESHOLD {
       morph_boss.current_state = State::revert();
       debug!("Morph boss: Revert state");
     }
@@ -373,7 +412,7 @@ fn revert_state(
 }
 
 fn rotate_towards_target(target_transform: &Transform, transform: &mut Transform) -> f32 {
-  let direction = target_transform.translation - transform.translation;
+  let direction = toroidal::shortest_delta(transform.translation, target_transform.translation);
   let target_angle = direction.y.atan2(direction.x);
   let current_angle = transform.rotation.to_euler(EulerRot::XYZ).2;
   let difference = (target_angle - current_angle).rem_euclid(2.0 * PI);
@@ -0,0 +1,159 @@
+use crate::enemies::calculate_lead_direction;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::shared::ProjectileInfo;
+use crate::shared_events::ProjectileSpawnEvent;
+use crate::timed_power_ups::WorldTime;
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use std::collections::VecDeque;
+use std::f32::consts::TAU;
+
+pub struct FiringPatternPlugin;
+
+impl Plugin for FiringPatternPlugin {
+  fn build(&self, app: &mut App) {
+    app.add_systems(Update, emitter_system.run_if(in_state(GameState::Playing)));
+  }
+}
+
+// The shape of a single volley - angles are in radians and relative to the emitter's facing direction (for fixed
+// patterns) or to the direction of the player (for aimed patterns)
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Pattern {
+  // Shots evenly spaced around a full circle
+  Ring { count: u16 },
+  // Like a ring but each shot turns further by the given angle, best combined with a shot delay
+  Spiral { count: u16, angle_per_shot: f32 },
+  // Shots aimed at the player one after another, re-aiming before each shot
+  AimedBurst { count: u16 },
+  // Shots fanned out evenly across the given angle, centred on the player
+  Spread { count: u16, angle: f32 },
+}
+
+// A complete firing pattern definition that can be referenced by any enemy through an `Emitter`
+#[derive(Clone, Debug)]
+pub(crate) struct FiringPattern {
+  pub pattern: Pattern,
+  pub projectile: ProjectileInfo,
+  pub cooldown: f32,       // Seconds between volleys
+  pub shot_delay: f32,     // Seconds between the individual shots of a volley
+  pub rotation: f32,       // Radians by which the whole pattern turns after each volley
+  pub spawn_distance: f32, // Distance from the emitter's centre at which projectiles spawn
+  pub accuracy: f32,       // Only used by aimed patterns, see `calculate_lead_direction`
+}
+
+#[derive(Clone, Copy)]
+struct Shot {
+  delay: f32,
+  angle: f32,
+  is_aimed: bool,
+}
+
+#[derive(Component)]
+pub(crate) struct Emitter {
+  firing_pattern: FiringPattern,
+  cooldown: f32,
+  rotation: f32,
+  queued_shots: VecDeque<Shot>,
+}
+
+impl Emitter {
+  pub fn new(firing_pattern: FiringPattern) -> Self {
+    Emitter {
+      cooldown: firing_pattern.cooldown,
+      firing_pattern,
+      rotation: 0.,
+      queued_shots: VecDeque::new(),
+    }
+  }
+
+  // Multiplies the rate of fire as well as the speed and damage of the projectiles, e.g. to make enemies stronger in
+  // later waves
+  pub fn scale(&mut self, fire_rate: f32, projectile_speed: f32, damage: f32) {
+    let projectile = &mut self.firing_pattern.projectile;
+    self.firing_pattern.cooldown /= fire_rate.max(0.1);
+    projectile.cooldown = self.firing_pattern.cooldown;
+    projectile.speed *= projectile_speed;
+    projectile.damage = ((projectile.damage as f32 * damage).round() as u16).max(1);
+  }
+}
+
+impl FiringPattern {
+  fn volley(&self, rotation: f32) -> Vec<Shot> {
+    let shot = |i: u16, angle: f32, is_aimed: bool| Shot {
+      delay: i as f32 * self.shot_delay,
+      angle: angle + rotation,
+      is_aimed,
+    };
+    match self.pattern {
+      Pattern::Ring { count } => (0..count)
+        .map(|i| shot(i, i as f32 * TAU / count as f32, false))
+        .collect(),
+      Pattern::Spiral { count, angle_per_shot } => {
+        (0..count).map(|i| shot(i, i as f32 * angle_per_shot, false)).collect()
+      }
+      Pattern::AimedBurst { count } => (0..count).map(|i| shot(i, 0., true)).collect(),
+      Pattern::Spread { count, angle } => (0..count)
+        .map(|i| {
+          let offset = if count > 1 {
+            -angle / 2. + angle * i as f32 / (count - 1) as f32
+          } else {
+            0.
+          };
+          shot(i, offset, true)
+        })
+        .collect(),
+    }
+  }
+}
+
+// Starts a new volley whenever an emitter's cooldown is over and fires each queued shot once its delay has passed
+fn emitter_system(
+  world_time: Res<WorldTime>,
+  mut emitter_query: Query<(&mut Emitter, &Transform), Without<Player>>,
+  player_query: Query<(&Transform, &Velocity), With<Player>>,
+  mut projectile_spawn_event: EventWriter<ProjectileSpawnEvent>,
+) {
+  let player = player_query.get_single().ok();
+  for (mut emitter, transform) in emitter_query.iter_mut() {
+    emitter.cooldown -= world_time.delta_seconds();
+    if emitter.cooldown <= 0. && emitter.queued_shots.is_empty() {
+      emitter.cooldown = emitter.firing_pattern.cooldown;
+      let volley = emitter.firing_pattern.volley(emitter.rotation);
+      emitter.queued_shots.extend(volley);
+      emitter.rotation = (emitter.rotation + emitter.firing_pattern.rotation) % TAU;
+    }
+
+    for shot in emitter.queued_shots.iter_mut() {
+      shot.delay -= world_time.delta_seconds();
+    }
+    while emitter.queued_shots.front().is_some_and(|shot| shot.delay <= 0.) {
+      let shot = emitter.queued_shots.pop_front().expect("Queued shot to exist");
+      let pattern = &emitter.firing_pattern;
+      let base_direction = match (shot.is_aimed, player) {
+        (true, Some((player_transform, player_velocity))) => calculate_lead_direction(
+          transform.translation,
+          player_transform.translation,
+          player_velocity.linvel,
+          pattern.projectile.speed,
+          pattern.accuracy,
+        ),
+        _ => Vec3::ZERO,
+      };
+      let base_direction = if base_direction == Vec3::ZERO {
+        transform.rotation * Vec3::Y
+      } else {
+        base_direction
+      };
+      let origin_forward = Quat::from_rotation_z(shot.angle) * base_direction;
+      projectile_spawn_event.send(ProjectileSpawnEvent {
+        projectile_info: pattern.projectile.clone(),
+        origin_rotation: transform.rotation,
+        origin_forward,
+        spawn_position: transform.translation + origin_forward * pattern.spawn_distance,
+      });
+    }
+  }
+}
@@ -0,0 +1,293 @@
+use crate::enemies::steering::{Approach, SpatialIndex, Steering};
+use crate::enemies::warp_in::{spawn_warp_in, WarpInEnemy};
+use crate::enemies::{move_toward_target, Enemy};
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::shared::{
+  default_enemy_collision_groups, mine_collision_groups, random_game_world_point,
+  random_game_world_point_away_from_player, Category, CollisionDamage, EntityType, ImpactInfo, Substance,
+  WrapAroundEntity, PURPLE, RED,
+};
+use crate::shared_events::{AreaDamageEvent, ExplosionEvent, WaveEvent};
+use crate::timed_power_ups::WorldTime;
+use crate::toroidal;
+use bevy::app::{App, FixedUpdate, Plugin};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::{
+  ActiveEvents, AdditionalMassProperties, Ccd, Collider, GravityScale, RigidBody, Velocity,
+};
+use std::f32::consts::PI;
+
+const SPEED: f32 = 40.;
+const HEALTH: i16 = 40;
+const DAMAGE: u16 = 5;
+const SCORE: u16 = 100;
+const RADIUS: f32 = 12.;
+const WAYPOINT_COUNT: usize = 4;
+const WAYPOINT_REACHED_DISTANCE: f32 = 20.;
+const MINE_DROP_COOLDOWN: f32 = 4.;
+const MAX_MINES_PER_LAYER: usize = 6;
+const MINE_HEALTH: i16 = 1;
+const MINE_SCORE: u16 = 10;
+const MINE_RADIUS: f32 = 4.;
+const MINE_ARMING_DELAY: f32 = 2.;
+const MINE_WARNING_RADIUS: f32 = 120.; // Distance from player at which an armed mine starts blinking
+const MINE_TRIGGER_RADIUS: f32 = 45.; // Distance from player at which an armed mine lights its fuse
+const MINE_FUSE: f32 = 0.8;
+const MINE_BLAST_RADIUS: f32 = 60.;
+const MINE_BLAST_DAMAGE: u16 = 8;
+
+pub struct MineLayerPlugin;
+
+impl Plugin for MineLayerPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(
+        FixedUpdate,
+        mine_layer_movement_system.run_if(in_state(GameState::Playing)),
+      )
+      .add_systems(
+        Update,
+        (mine_laying_system, mine_system).run_if(in_state(GameState::Playing)),
+      );
+  }
+}
+
+#[derive(Component)]
+struct MineLayer {
+  waypoints: Vec<Vec3>,
+  current_waypoint: usize,
+  mine_cooldown: f32,
+}
+
+#[derive(Component)]
+pub(crate) struct Mine {
+  layer: Entity,
+  arming_timer: Timer,
+  fuse: Option<Timer>,
+  blink_timer: f32,
+}
+
+pub fn spawn_mine_layer_wave(event: &WaveEvent, commands: &mut Commands, asset_server: &Res<AssetServer>) {
+  for _ in 0..event.mine_layer_count {
+    let spawn_point = random_game_world_point_away_from_player(event.player_position, 300.);
+    spawn_warp_in(commands, asset_server, spawn_point, WarpInEnemy::MineLayer);
+  }
+}
+
+pub(crate) fn spawn_mine_layer(commands: &mut Commands, spawn_point: Vec3) {
+  let shape = shapes::Polygon {
+    points: (0..6).map(|i| Vec2::from_angle(i as f32 * PI / 3.) * RADIUS).collect(),
+    closed: true,
+  };
+  let mut waypoints = vec![spawn_point];
+  waypoints.extend((1..WAYPOINT_COUNT).map(|_| random_game_world_point()));
+  commands
+    .spawn((
+      ShapeBundle {
+        path: GeometryBuilder::build_as(&shape),
+        spatial: SpatialBundle {
+          transform: Transform::from_translation(spawn_point),
+          ..default()
+        },
+        ..default()
+      },
+      Stroke::new(PURPLE, 1.5),
+      Name::new("Mine Layer"),
+      RigidBody::Dynamic,
+      Collider::ball(RADIUS),
+      ActiveEvents::COLLISION_EVENTS,
+      ImpactInfo {
+        impact_category: Category::S,
+        death_category: Category::M,
+        substance: Substance::Metal,
+      },
+      GravityScale(0.),
+      Velocity {
+        linvel: Vec2::ZERO,
+        angvel: 0.8,
+      },
+      AdditionalMassProperties::Mass(8.),
+      default_enemy_collision_groups(),
+      Enemy {
+        health_points: HEALTH,
+        movement_speed: SPEED,
+        score_points: SCORE,
+      },
+      MineLayer {
+        waypoints,
+        current_waypoint: 1,
+        mine_cooldown: MINE_DROP_COOLDOWN,
+      },
+      Steering {
+        radius: RADIUS,
+        approach: Approach::Arrive { slowing_radius: 60. },
+      },
+      CollisionDamage::new(DAMAGE),
+    ))
+    .insert((Ccd::enabled(), WrapAroundEntity));
+}
+
+fn spawn_mine(commands: &mut Commands, spawn_point: Vec3, layer: Entity) {
+  commands.spawn((
+    ShapeBundle {
+      path: GeometryBuilder::build_as(&shapes::Circle {
+        radius: MINE_RADIUS,
+        center: Vec2::ZERO,
+      }),
+      spatial: SpatialBundle {
+        transform: Transform::from_translation(spawn_point),
+        ..default()
+      },
+      ..default()
+    },
+    Fill::color(RED.with_alpha(0.4)),
+    Name::new("Mine"),
+    RigidBody::Fixed,
+    Collider::ball(MINE_RADIUS),
+    ActiveEvents::COLLISION_EVENTS,
+    ImpactInfo {
+      impact_category: Category::S,
+      death_category: Category::S,
+      substance: Substance::Metal,
+    },
+    mine_collision_groups(),
+    Enemy {
+      health_points: MINE_HEALTH,
+      movement_speed: 0.,
+      score_points: MINE_SCORE,
+    },
+    Mine {
+      layer,
+      arming_timer: Timer::from_seconds(MINE_ARMING_DELAY, TimerMode::Once),
+      fuse: None,
+      blink_timer: 0.,
+    },
+    CollisionDamage::new(MINE_BLAST_DAMAGE),
+    WrapAroundEntity,
+  ));
+}
+
+// Mine layers patrol between their waypoints, slowing down as they reach each one
+fn mine_layer_movement_system(
+  mut mine_layer_query: Query<(Entity, &Transform, &mut Velocity, &Enemy, &Steering, &mut MineLayer)>,
+  spatial_index: Res<SpatialIndex>,
+) {
+  for (entity, transform, mut velocity, enemy, steering, mut mine_layer) in mine_layer_query.iter_mut() {
+    let waypoint = mine_layer.waypoints[mine_layer.current_waypoint];
+    if toroidal::distance(transform.translation, waypoint) < WAYPOINT_REACHED_DISTANCE {
+      mine_layer.current_waypoint = (mine_layer.current_waypoint + 1) % mine_layer.waypoints.len();
+    }
+    move_toward_target(
+      entity,
+      &Transform::from_translation(waypoint),
+      transform,
+      &mut *velocity,
+      enemy.movement_speed,
+      steering,
+      &spatial_index,
+    );
+  }
+}
+
+fn mine_laying_system(
+  mut commands: Commands,
+  world_time: Res<WorldTime>,
+  mut mine_layer_query: Query<(Entity, &Transform, &mut MineLayer)>,
+  mine_query: Query<&Mine>,
+) {
+  for (entity, transform, mut mine_layer) in mine_layer_query.iter_mut() {
+    mine_layer.mine_cooldown -= world_time.delta_seconds();
+    if mine_layer.mine_cooldown > 0. {
+      continue;
+    }
+    mine_layer.mine_cooldown = MINE_DROP_COOLDOWN;
+    if mine_query.iter().filter(|mine| mine.layer == entity).count() >= MAX_MINES_PER_LAYER {
+      continue;
+    }
+    spawn_mine(&mut commands, transform.translation.with_z(-1.), entity);
+    debug!("Mine dropped at {:?}", transform.translation);
+  }
+}
+
+// Mines arm after a delay, blink faster the closer the player gets and detonate shortly after the player comes within
+// the trigger radius, damaging everything in the blast radius
+fn mine_system(
+  mut commands: Commands,
+  world_time: Res<WorldTime>,
+  mut mine_query: Query<(Entity, &Transform, &mut Mine, &mut Fill, &mut Visibility)>,
+  player_query: Query<&Transform, With<Player>>,
+  mut area_damage_event: EventWriter<AreaDamageEvent>,
+  mut explosion_event: EventWriter<ExplosionEvent>,
+) {
+  let player_position = player_query.get_single().ok().map(|transform| transform.translation);
+  for (entity, transform, mut mine, mut fill, mut visibility) in mine_query.iter_mut() {
+    if !mine.arming_timer.finished() {
+      mine.arming_timer.tick(world_time.delta());
+      if mine.arming_timer.just_finished() {
+        fill.color = RED;
+      }
+      continue;
+    }
+
+    let distance = player_position.map(|player| toroidal::distance(transform.translation, player));
+    if mine.fuse.is_none() && distance.is_some_and(|distance| distance < MINE_TRIGGER_RADIUS) {
+      mine.fuse = Some(Timer::from_seconds(MINE_FUSE, TimerMode::Once));
+    }
+
+    if let Some(fuse) = mine.fuse.as_mut() {
+      if fuse.tick(world_time.delta()).finished() {
+        detonate(
+          &mut commands,
+          entity,
+          transform,
+          &mut area_damage_event,
+          &mut explosion_event,
+        );
+        continue;
+      }
+    }
+
+    let blink_interval = match (&mine.fuse, distance) {
+      (Some(_), _) => Some(0.05),
+      (None, Some(distance)) if distance < MINE_WARNING_RADIUS => Some(0.4 * distance / MINE_WARNING_RADIUS),
+      _ => None,
+    };
+    match blink_interval {
+      Some(interval) => {
+        mine.blink_timer -= world_time.delta_seconds();
+        if mine.blink_timer <= 0. {
+          mine.blink_timer = interval;
+          *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+          };
+        }
+      }
+      None => *visibility = Visibility::Inherited,
+    }
+  }
+}
+
+fn detonate(
+  commands: &mut Commands,
+  entity: Entity,
+  transform: &Transform,
+  area_damage_event: &mut EventWriter<AreaDamageEvent>,
+  explosion_event: &mut EventWriter<ExplosionEvent>,
+) {
+  commands.entity(entity).despawn();
+  area_damage_event.send(AreaDamageEvent {
+    origin: transform.translation,
+    radius: MINE_BLAST_RADIUS,
+    damage: MINE_BLAST_DAMAGE,
+    by: EntityType::Mine,
+  });
+  explosion_event.send(ExplosionEvent {
+    origin: transform.translation,
+    category: Category::L,
+    substance: Substance::Metal,
+  });
+  info!("Mine detonated at {:?}", transform.translation);
+}
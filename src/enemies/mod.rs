@@ -1,19 +1,32 @@
 use crate::enemies::boss_morph::MorphBossPlugin;
+use crate::enemies::firing_pattern::FiringPatternPlugin;
+use crate::enemies::mine_layer::MineLayerPlugin;
+use crate::enemies::scaling::EnemyScalingPlugin;
+use crate::enemies::steering::{arrive, seek, steer, Approach, SpatialIndex, Steering, SteeringPlugin};
+use crate::enemies::swarm::SwarmPlugin;
 use crate::enemies::ufo::UfoPlugin;
+use crate::enemies::warp_in::WarpInPlugin;
 use crate::game_state::GameState;
 use crate::shared::ImpactInfo;
 use crate::shared_events::{EnemyDamageEvent, ExplosionEvent, NextWaveEvent, ScoreEvent};
+use crate::toroidal;
 use bevy::app::{App, Plugin, Update};
 use bevy::core::Name;
 use bevy::log::{debug, info};
 use bevy::prelude::{
   in_state, Commands, Component, Entity, EventReader, EventWriter, IntoSystemConfigs, OnEnter, Query, Transform, Vec2,
-  With,
+  Vec3, With,
 };
 use bevy_rapier2d::prelude::Velocity;
 
 pub(crate) mod boss_morph;
+pub(crate) mod firing_pattern;
+pub(crate) mod mine_layer;
+pub(crate) mod scaling;
+pub(crate) mod steering;
+pub(crate) mod swarm;
 pub(crate) mod ufo;
+pub(crate) mod warp_in;
 
 pub struct EnemyPlugin;
 
@@ -21,7 +34,16 @@ impl Plugin for EnemyPlugin {
   fn build(&self, app: &mut App) {
     app
       .add_event::<EnemyDamageEvent>()
-      .add_plugins((UfoPlugin, MorphBossPlugin))
+      .add_plugins((
+        UfoPlugin,
+        MorphBossPlugin,
+        SwarmPlugin,
+        MineLayerPlugin,
+        FiringPatternPlugin,
+        WarpInPlugin,
+        EnemyScalingPlugin,
+        SteeringPlugin,
+      ))
       .add_systems(OnEnter(GameState::Starting), reset_enemies_system)
       .add_systems(
         Update,
@@ -96,12 +118,134 @@ fn next_wave_event(
 }
 
 pub(crate) fn move_toward_target(
+  entity: Entity,
   target_transform: &Transform,
   transform: &Transform,
   velocity: &mut Velocity,
   speed: f32,
+  steering: &Steering,
+  spatial_index: &SpatialIndex,
 ) {
-  let direction = target_transform.translation - transform.translation;
-  let direction = direction / direction.length();
-  velocity.linvel = Vec2::new(direction.x * speed, direction.y * speed);
+  let position = transform.translation.truncate();
+  let target = position + toroidal::shortest_delta(transform.translation, target_transform.translation).truncate();
+  let desired_velocity = match steering.approach {
+    Approach::Seek => seek(position, target, speed),
+    Approach::Arrive { slowing_radius } => arrive(position, target, speed, slowing_radius),
+  };
+  velocity.linvel = steer(entity, steering, position, desired_velocity, speed, spatial_index);
+}
+
+// Returns the direction in which a projectile with the given speed needs to be fired to intercept a target that keeps
+// moving at its current velocity. The accuracy (0 to 1) blends between aiming at the target's current position and
+// aiming at the predicted intercept point.
+pub(crate) fn calculate_lead_direction(
+  origin: Vec3,
+  target_position: Vec3,
+  target_velocity: Vec2,
+  projectile_speed: f32,
+  accuracy: f32,
+) -> Vec3 {
+  let delta = toroidal::shortest_delta(origin, target_position).truncate();
+  let aim_point = match calculate_intercept_time(delta, target_velocity, projectile_speed) {
+    Some(time) => delta + target_velocity * time * accuracy.clamp(0., 1.),
+    None => delta,
+  };
+  aim_point.normalize_or_zero().extend(0.)
+}
+
+// Solves |delta + velocity * t| = speed * t for the smallest positive t, if there is one
+fn calculate_intercept_time(delta: Vec2, velocity: Vec2, speed: f32) -> Option<f32> {
+  let a = velocity.length_squared() - speed * speed;
+  let b = 2. * delta.dot(velocity);
+  let c = delta.length_squared();
+  if a.abs() < f32::EPSILON {
+    let time = -c / b;
+    return if b < 0. && time > 0. { Some(time) } else { None };
+  }
+  let discriminant = b * b - 4. * a * c;
+  if discriminant < 0. {
+    return None;
+  }
+  let root = discriminant.sqrt();
+  let t1 = (-b - root) / (2. * a);
+  let t2 = (-b + root) / (2. * a);
+  match (t1 > 0., t2 > 0.) {
+    (true, true) => Some(t1.min(t2)),
+    (true, false) => Some(t1),
+    (false, true) => Some(t2),
+    (false, false) => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::{assert_approx_eq, assert_vec3_approx_eq};
+
+  #[test]
+  fn intercept_time_for_stationary_target_is_distance_over_speed() {
+    assert_eq!(calculate_intercept_time(Vec2::new(100., 0.), Vec2::ZERO, 50.), Some(2.));
+  }
+
+  #[test]
+  fn intercept_time_for_target_approaching_at_projectile_speed() {
+    assert_eq!(
+      calculate_intercept_time(Vec2::new(100., 0.), Vec2::new(-50., 0.), 50.),
+      Some(1.)
+    );
+  }
+
+  #[test]
+  fn intercept_time_is_none_for_target_outrunning_projectile() {
+    assert_eq!(
+      calculate_intercept_time(Vec2::new(100., 0.), Vec2::new(100., 0.), 50.),
+      None
+    );
+    assert_eq!(
+      calculate_intercept_time(Vec2::new(100., 0.), Vec2::new(50., 0.), 50.),
+      None
+    );
+  }
+
+  #[test]
+  fn intercept_time_reaches_target_and_projectile_at_the_same_point() {
+    let (delta, velocity, speed) = (Vec2::new(100., 0.), Vec2::new(0., 30.), 50.);
+    let time = calculate_intercept_time(delta, velocity, speed).expect("Intercept to exist");
+    assert!(((delta + velocity * time).length() - speed * time).abs() < 1e-3);
+    assert_approx_eq(time, 2.5);
+  }
+
+  #[test]
+  fn lead_direction_without_accuracy_aims_at_current_position() {
+    let direction = calculate_lead_direction(Vec3::ZERO, Vec3::new(100., 0., 0.), Vec2::new(0., 30.), 50., 0.);
+    assert_vec3_approx_eq(direction, Vec3::X);
+  }
+
+  #[test]
+  fn lead_direction_with_full_accuracy_aims_at_intercept_point() {
+    let direction = calculate_lead_direction(Vec3::ZERO, Vec3::new(100., 0., 0.), Vec2::new(0., 30.), 50., 1.);
+    assert_vec3_approx_eq(direction, Vec3::new(0.8, 0.6, 0.));
+  }
+
+  #[test]
+  fn lead_direction_clamps_accuracy() {
+    let target = Vec3::new(100., 0., 0.);
+    let velocity = Vec2::new(0., 30.);
+    assert_vec3_approx_eq(
+      calculate_lead_direction(Vec3::ZERO, target, velocity, 50., 5.),
+      calculate_lead_direction(Vec3::ZERO, target, velocity, 50., 1.),
+    );
+  }
+
+  #[test]
+  fn lead_direction_falls_back_to_current_position_without_intercept() {
+    let direction = calculate_lead_direction(Vec3::ZERO, Vec3::new(0., 100., 0.), Vec2::new(0., 200.), 50., 1.);
+    assert_vec3_approx_eq(direction, Vec3::Y);
+  }
+
+  #[test]
+  fn lead_direction_aims_across_the_world_edge() {
+    let direction = calculate_lead_direction(Vec3::new(480., 0., 0.), Vec3::new(-480., 0., 0.), Vec2::ZERO, 50., 1.);
+    assert_vec3_approx_eq(direction, Vec3::X);
+  }
 }
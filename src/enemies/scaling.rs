@@ -0,0 +1,170 @@
+use crate::difficulty::Difficulty;
+use crate::enemies::firing_pattern::Emitter;
+use crate::enemies::Enemy;
+use crate::game_state::GameState;
+use crate::shared::CollisionDamage;
+use crate::shared_resources::Wave;
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+
+pub struct EnemyScalingPlugin;
+
+impl Plugin for EnemyScalingPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .register_type::<EnemyScaling>()
+      .init_resource::<EnemyScaling>()
+      .add_systems(
+        Update,
+        scale_spawned_enemies_system.run_if(in_state(GameState::Playing)),
+      );
+  }
+}
+
+// A multiplier of `1 + growth_per_wave * (wave - 1) ^ exponent`, capped at `max_multiplier`, which is then adjusted by
+// the difficulty according to the curve's difficulty weight
+#[derive(Debug, Clone, Copy, Reflect)]
+pub(crate) struct ScalingCurve {
+  pub growth_per_wave: f32,
+  pub exponent: f32,
+  pub max_multiplier: f32,
+  pub difficulty_weight: f32,
+}
+
+impl ScalingCurve {
+  pub fn multiplier(&self, wave: u16, difficulty: f32) -> f32 {
+    let wave_multiplier = 1. + self.growth_per_wave * (wave.saturating_sub(1) as f32).powf(self.exponent);
+    let difficulty_multiplier = 1. + (difficulty - 1.) * self.difficulty_weight;
+    wave_multiplier.min(self.max_multiplier) * difficulty_multiplier.max(0.1)
+  }
+}
+
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct EnemyScaling {
+  pub difficulty: f32, // 1 is the baseline, higher values make enemies stronger
+  pub health: ScalingCurve,
+  pub movement_speed: ScalingCurve,
+  pub projectile_speed: ScalingCurve,
+  pub fire_rate: ScalingCurve,
+}
+
+impl Default for EnemyScaling {
+  fn default() -> Self {
+    EnemyScaling {
+      difficulty: 1.,
+      health: ScalingCurve {
+        growth_per_wave: 0.08,
+        exponent: 1.,
+        max_multiplier: 4.,
+        difficulty_weight: 1.,
+      },
+      movement_speed: ScalingCurve {
+        growth_per_wave: 0.03,
+        exponent: 1.,
+        max_multiplier: 1.6,
+        difficulty_weight: 0.5,
+      },
+      projectile_speed: ScalingCurve {
+        growth_per_wave: 0.03,
+        exponent: 1.,
+        max_multiplier: 1.8,
+        difficulty_weight: 0.5,
+      },
+      fire_rate: ScalingCurve {
+        growth_per_wave: 0.04,
+        exponent: 1.,
+        max_multiplier: 2.,
+        difficulty_weight: 0.75,
+      },
+    }
+  }
+}
+
+// Applies the scaling curves for the current wave to enemies when they are spawned, as well as the damage multiplier
+// of the selected difficulty
+fn scale_spawned_enemies_system(
+  mut query: Query<(&mut Enemy, Option<&mut Emitter>, Option<&mut CollisionDamage>, &Name), Added<Enemy>>,
+  scaling: Res<EnemyScaling>,
+  wave: Res<Wave>,
+  difficulty: Res<Difficulty>,
+) {
+  let damage = difficulty.enemy_damage();
+  for (mut enemy, emitter, collision_damage, name) in query.iter_mut() {
+    let health = scaling.health.multiplier(wave.0, scaling.difficulty);
+    let movement_speed = scaling.movement_speed.multiplier(wave.0, scaling.difficulty);
+    enemy.health_points = ((enemy.health_points as f32 * health).round() as i16).max(1);
+    enemy.movement_speed *= movement_speed;
+    if let Some(mut emitter) = emitter {
+      emitter.scale(
+        scaling.fire_rate.multiplier(wave.0, scaling.difficulty),
+        scaling.projectile_speed.multiplier(wave.0, scaling.difficulty),
+        damage,
+      );
+    }
+    if let Some(mut collision_damage) = collision_damage {
+      collision_damage.damage = ((collision_damage.damage as f32 * damage).round() as u16).max(1);
+    }
+    debug!(
+      "Scaled {:?} for wave {} to {} health and {} speed",
+      name, wave.0, enemy.health_points, enemy.movement_speed
+    );
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::assert_approx_eq;
+
+  const CURVE: ScalingCurve = ScalingCurve {
+    growth_per_wave: 0.1,
+    exponent: 2.,
+    max_multiplier: 3.,
+    difficulty_weight: 0.5,
+  };
+
+  #[test]
+  fn first_wave_at_baseline_difficulty_is_unscaled() {
+    assert_approx_eq(CURVE.multiplier(1, 1.), 1.);
+    assert_approx_eq(CURVE.multiplier(0, 1.), 1.);
+  }
+
+  #[test]
+  fn multiplier_grows_with_the_wave_along_the_curve() {
+    assert_approx_eq(CURVE.multiplier(2, 1.), 1.1);
+    assert_approx_eq(CURVE.multiplier(4, 1.), 1.9);
+  }
+
+  #[test]
+  fn wave_multiplier_is_capped() {
+    assert_approx_eq(CURVE.multiplier(50, 1.), 3.);
+  }
+
+  #[test]
+  fn difficulty_is_weighted() {
+    assert_approx_eq(CURVE.multiplier(1, 2.), 1.5);
+    assert_approx_eq(CURVE.multiplier(1, 0.5), 0.75);
+    assert_approx_eq(CURVE.multiplier(50, 2.), 4.5);
+  }
+
+  #[test]
+  fn difficulty_multiplier_never_drops_below_the_minimum() {
+    assert_approx_eq(CURVE.multiplier(1, -10.), 0.1);
+  }
+
+  #[test]
+  fn default_curves_never_shrink_enemies_as_waves_progress() {
+    let scaling = EnemyScaling::default();
+    for curve in [
+      scaling.health,
+      scaling.movement_speed,
+      scaling.projectile_speed,
+      scaling.fire_rate,
+    ] {
+      let multipliers: Vec<f32> = (1..=100).map(|wave| curve.multiplier(wave, 1.)).collect();
+      assert!(multipliers.windows(2).all(|pair| pair[0] <= pair[1]));
+      assert_approx_eq(multipliers[99], curve.max_multiplier);
+    }
+  }
+}
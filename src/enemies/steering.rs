@@ -0,0 +1,363 @@
+use crate::asteroids::Asteroid;
+use crate::enemies::Enemy;
+use crate::game_state::GameState;
+use crate::game_world::WORLD_SIZE;
+use crate::toroidal;
+use bevy::app::{App, FixedPreUpdate, Plugin};
+use bevy::math::{IVec2, Vec2};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+const CELL_SIZE: f32 = 100.; // Must result in a whole number when dividing WORLD_SIZE by it
+const LOOK_AHEAD_DISTANCE: f32 = 80.; // How far ahead enemies look for obstacles in their path
+const CLEARANCE: f32 = 8.; // Additional space enemies try to keep between themselves and obstacles
+const SEPARATION_DISTANCE: f32 = 20.; // Additional space enemies try to keep between each other
+const AVOIDANCE_WEIGHT: f32 = 2.;
+const SEPARATION_WEIGHT: f32 = 1.;
+
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin {
+  fn build(&self, app: &mut App) {
+    app.init_resource::<SpatialIndex>().add_systems(
+      FixedPreUpdate,
+      update_spatial_index_system.run_if(in_state(GameState::Playing)),
+    );
+  }
+}
+
+#[derive(Component, Clone, Copy)]
+pub(crate) struct Steering {
+  pub radius: f32,
+  pub approach: Approach,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Approach {
+  Seek,
+  Arrive { slowing_radius: f32 },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum BodyKind {
+  Asteroid,
+  Enemy,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Body {
+  pub entity: Entity,
+  pub position: Vec2,
+  pub radius: f32,
+  pub kind: BodyKind,
+}
+
+// A uniform grid of asteroid and enemy positions, rebuilt every fixed update, so that enemies only need to look at
+// bodies in their vicinity - the grid wraps around just like the game world
+#[derive(Resource, Default)]
+pub(crate) struct SpatialIndex {
+  cells: HashMap<IVec2, Vec<Body>>,
+}
+
+impl SpatialIndex {
+  fn cell(position: Vec2) -> IVec2 {
+    let cells_per_side = (WORLD_SIZE / CELL_SIZE) as i32;
+    let cell = ((position + Vec2::splat(WORLD_SIZE / 2.)) / CELL_SIZE)
+      .floor()
+      .as_ivec2();
+    IVec2::new(cell.x.rem_euclid(cells_per_side), cell.y.rem_euclid(cells_per_side))
+  }
+
+  fn insert(&mut self, body: Body) {
+    self.cells.entry(Self::cell(body.position)).or_default().push(body);
+  }
+
+  // Returns all bodies whose outline is within the given radius of the position, with each body's position moved to
+  // wherever it is closest to the position (which may be across the edge of the world)
+  pub fn query(&self, position: Vec2, radius: f32) -> impl Iterator<Item = Body> + '_ {
+    let cells_per_side = (WORLD_SIZE / CELL_SIZE) as i32;
+    let reach = ((radius / CELL_SIZE).ceil() as i32 + 1).min((cells_per_side - 1) / 2);
+    let centre = Self::cell(position);
+    (-reach..=reach)
+      .flat_map(move |x| (-reach..=reach).map(move |y| IVec2::new(x, y)))
+      .map(move |offset| (centre + offset).rem_euclid(IVec2::splat(cells_per_side)))
+      .filter_map(|cell| self.cells.get(&cell))
+      .flatten()
+      .map(move |body| Body {
+        position: position + toroidal::shortest_delta(position.extend(0.), body.position.extend(0.)).truncate(),
+        ..*body
+      })
+      .filter(move |body| (body.position - position).length() - body.radius <= radius)
+  }
+}
+
+fn update_spatial_index_system(
+  mut spatial_index: ResMut<SpatialIndex>,
+  asteroid_query: Query<(Entity, &Transform, &Asteroid)>,
+  enemy_query: Query<(Entity, &Transform, &Steering), With<Enemy>>,
+) {
+  spatial_index.cells.clear();
+  for (entity, transform, asteroid) in asteroid_query.iter() {
+    spatial_index.insert(Body {
+      entity,
+      position: transform.translation.truncate(),
+      radius: asteroid.radius(),
+      kind: BodyKind::Asteroid,
+    });
+  }
+  for (entity, transform, steering) in enemy_query.iter() {
+    spatial_index.insert(Body {
+      entity,
+      position: transform.translation.truncate(),
+      radius: steering.radius,
+      kind: BodyKind::Enemy,
+    });
+  }
+}
+
+// Desired velocity to move towards the target at full speed
+pub(crate) fn seek(position: Vec2, target: Vec2, max_speed: f32) -> Vec2 {
+  (target - position).normalize_or_zero() * max_speed
+}
+
+// Desired velocity to move towards the target, slowing down when getting closer than the slowing radius
+pub(crate) fn arrive(position: Vec2, target: Vec2, max_speed: f32, slowing_radius: f32) -> Vec2 {
+  let offset = target - position;
+  let distance = offset.length();
+  let speed = if distance < slowing_radius {
+    max_speed * distance / slowing_radius
+  } else {
+    max_speed
+  };
+  offset.normalize_or_zero() * speed
+}
+
+// Steers towards the average position of the neighbours
+pub(crate) fn cohesion(position: Vec2, neighbour_positions: &[Vec2]) -> Vec2 {
+  if neighbour_positions.is_empty() {
+    return Vec2::ZERO;
+  }
+  let centre = neighbour_positions.iter().sum::<Vec2>() / neighbour_positions.len() as f32;
+  (centre - position).normalize_or_zero()
+}
+
+// Steers towards the average heading of the neighbours
+pub(crate) fn alignment(neighbour_velocities: &[Vec2]) -> Vec2 {
+  neighbour_velocities.iter().sum::<Vec2>().normalize_or_zero()
+}
+
+// Pushes the agent away from nearby bodies that are closer than the separation distance
+pub(crate) fn separation(position: Vec2, radius: f32, neighbours: impl Iterator<Item = Body>) -> Vec2 {
+  let mut force = Vec2::ZERO;
+  for neighbour in neighbours {
+    let offset = position - neighbour.position;
+    let limit = radius + neighbour.radius + SEPARATION_DISTANCE;
+    let distance = offset.length();
+    if distance < limit {
+      force += offset.normalize_or_zero() * (1. - distance / limit);
+    }
+  }
+  force
+}
+
+// Pushes the agent sideways if an obstacle lies in its path within the look ahead distance
+pub(crate) fn avoid_obstacles(
+  position: Vec2,
+  direction: Vec2,
+  radius: f32,
+  obstacles: impl Iterator<Item = Body>,
+) -> Vec2 {
+  let direction = direction.normalize_or_zero();
+  if direction == Vec2::ZERO {
+    return Vec2::ZERO;
+  }
+  let mut force = Vec2::ZERO;
+  for obstacle in obstacles {
+    let offset = obstacle.position - position;
+    let ahead = offset.dot(direction);
+    if ahead < 0. || ahead > LOOK_AHEAD_DISTANCE {
+      continue;
+    }
+    let lateral = offset - direction * ahead;
+    let clearance = obstacle.radius + radius + CLEARANCE;
+    let lateral_distance = lateral.length();
+    if lateral_distance < clearance {
+      let away = if lateral_distance > f32::EPSILON {
+        -lateral / lateral_distance
+      } else {
+        direction.perp()
+      };
+      let urgency = 1. - ahead / LOOK_AHEAD_DISTANCE;
+      force += away * urgency * (1. - lateral_distance / clearance);
+    }
+  }
+  force
+}
+
+// Combines the desired velocity with separation from other enemies and avoidance of asteroids
+pub(crate) fn steer(
+  entity: Entity,
+  steering: &Steering,
+  position: Vec2,
+  desired_velocity: Vec2,
+  max_speed: f32,
+  spatial_index: &SpatialIndex,
+) -> Vec2 {
+  let nearby: Vec<Body> = spatial_index
+    .query(position, steering.radius + LOOK_AHEAD_DISTANCE)
+    .filter(|body| body.entity != entity)
+    .collect();
+  let separation = separation(
+    position,
+    steering.radius,
+    nearby.iter().copied().filter(|body| body.kind == BodyKind::Enemy),
+  );
+  let avoidance = avoid_obstacles(
+    position,
+    desired_velocity,
+    steering.radius,
+    nearby.iter().copied().filter(|body| body.kind == BodyKind::Asteroid),
+  );
+  (desired_velocity + (separation * SEPARATION_WEIGHT + avoidance * AVOIDANCE_WEIGHT) * max_speed)
+    .clamp_length_max(max_speed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::{assert_approx_eq, assert_vec3_approx_eq};
+
+  fn body(index: u32, position: Vec2, radius: f32, kind: BodyKind) -> Body {
+    Body {
+      entity: Entity::from_raw(index),
+      position,
+      radius,
+      kind,
+    }
+  }
+
+  fn spatial_index(bodies: &[Body]) -> SpatialIndex {
+    let mut spatial_index = SpatialIndex::default();
+    for body in bodies {
+      spatial_index.insert(*body);
+    }
+    spatial_index
+  }
+
+  fn assert_vec2_approx_eq(actual: Vec2, expected: Vec2) {
+    assert_vec3_approx_eq(actual.extend(0.), expected.extend(0.));
+  }
+
+  #[test]
+  fn seek_moves_towards_the_target_at_full_speed() {
+    assert_vec2_approx_eq(seek(Vec2::ZERO, Vec2::new(0., 300.), 50.), Vec2::new(0., 50.));
+    assert_eq!(seek(Vec2::ONE, Vec2::ONE, 50.), Vec2::ZERO);
+  }
+
+  #[test]
+  fn arrive_slows_down_inside_the_slowing_radius() {
+    assert_vec2_approx_eq(arrive(Vec2::ZERO, Vec2::new(300., 0.), 50., 100.), Vec2::new(50., 0.));
+    assert_vec2_approx_eq(arrive(Vec2::ZERO, Vec2::new(50., 0.), 50., 100.), Vec2::new(25., 0.));
+    assert_vec2_approx_eq(arrive(Vec2::ZERO, Vec2::new(10., 0.), 50., 100.), Vec2::new(5., 0.));
+    assert_eq!(arrive(Vec2::ONE, Vec2::ONE, 50., 100.), Vec2::ZERO);
+  }
+
+  #[test]
+  fn separation_pushes_away_from_close_neighbours_only() {
+    let close = body(1, Vec2::new(10., 0.), 5., BodyKind::Enemy);
+    let distant = body(2, Vec2::new(0., 100.), 5., BodyKind::Enemy);
+    let force = separation(Vec2::ZERO, 5., [close, distant].into_iter());
+    assert_vec2_approx_eq(force, Vec2::new(-(1. - 10. / 30.), 0.));
+  }
+
+  #[test]
+  fn separation_pushes_apart_across_the_world_edge() {
+    let spatial_index = spatial_index(&[body(1, Vec2::new(-495., 0.), 5., BodyKind::Enemy)]);
+    let position = Vec2::new(495., 0.);
+    let force = separation(position, 5., spatial_index.query(position, 50.));
+    assert!(
+      force.x < 0.,
+      "Expected to be pushed away from the edge but was {:?}",
+      force
+    );
+    assert_approx_eq(force.y, 0.);
+  }
+
+  #[test]
+  fn avoidance_pushes_sideways_when_an_obstacle_is_ahead() {
+    let obstacle = body(1, Vec2::new(5., 40.), 10., BodyKind::Asteroid);
+    let force = avoid_obstacles(Vec2::ZERO, Vec2::Y, 5., [obstacle].into_iter());
+    assert!(force.x < 0., "Expected to be pushed to the left but was {:?}", force);
+    assert_approx_eq(force.y, 0.);
+  }
+
+  #[test]
+  fn avoidance_ignores_obstacles_that_are_not_ahead() {
+    let behind = body(1, Vec2::new(0., -40.), 10., BodyKind::Asteroid);
+    let beyond_look_ahead = body(2, Vec2::new(0., LOOK_AHEAD_DISTANCE + 10.), 10., BodyKind::Asteroid);
+    let beside = body(3, Vec2::new(50., 40.), 10., BodyKind::Asteroid);
+    let obstacles = [behind, beyond_look_ahead, beside];
+    assert_eq!(
+      avoid_obstacles(Vec2::ZERO, Vec2::Y, 5., obstacles.into_iter()),
+      Vec2::ZERO
+    );
+    assert_eq!(
+      avoid_obstacles(Vec2::ZERO, Vec2::ZERO, 5., obstacles.into_iter()),
+      Vec2::ZERO
+    );
+  }
+
+  #[test]
+  fn spatial_index_returns_neighbours_in_adjacent_cells() {
+    let spatial_index = spatial_index(&[
+      body(1, Vec2::new(95., 0.), 5., BodyKind::Enemy),
+      body(2, Vec2::new(105., 0.), 5., BodyKind::Enemy),
+      body(3, Vec2::new(300., 0.), 5., BodyKind::Enemy),
+    ]);
+    let mut entities: Vec<u32> = spatial_index
+      .query(Vec2::new(101., 0.), 20.)
+      .map(|body| body.entity.index())
+      .collect();
+    entities.sort();
+    assert_eq!(entities, vec![1, 2]);
+  }
+
+  #[test]
+  fn spatial_index_returns_neighbours_across_the_world_edge() {
+    let spatial_index = spatial_index(&[body(1, Vec2::new(-495., 490.), 5., BodyKind::Asteroid)]);
+    let neighbours: Vec<Body> = spatial_index.query(Vec2::new(495., -495.), 30.).collect();
+    assert_eq!(neighbours.len(), 1);
+    assert_vec2_approx_eq(neighbours[0].position, Vec2::new(505., -510.));
+  }
+
+  #[test]
+  fn steering_ignores_itself_and_never_exceeds_the_maximum_speed() {
+    let steering = Steering {
+      radius: 5.,
+      approach: Approach::Seek,
+    };
+    let spatial_index = spatial_index(&[
+      body(1, Vec2::ZERO, 5., BodyKind::Enemy),
+      body(2, Vec2::new(10., 0.), 5., BodyKind::Enemy),
+    ]);
+    let velocity = steer(
+      Entity::from_raw(2),
+      &steering,
+      Vec2::new(10., 0.),
+      Vec2::new(0., 50.),
+      50.,
+      &spatial_index,
+    );
+    assert!(velocity.x > 0.);
+    assert_approx_eq(velocity.length(), 50.);
+    let velocity = steer(
+      Entity::from_raw(3),
+      &steering,
+      Vec2::new(0., 200.),
+      Vec2::new(0., 50.),
+      50.,
+      &spatial_index,
+    );
+    assert_vec2_approx_eq(velocity, Vec2::new(0., 50.));
+  }
+}
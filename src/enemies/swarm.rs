@@ -0,0 +1,174 @@
+use crate::enemies::steering::{alignment, cohesion, seek, steer, Approach, SpatialIndex, Steering};
+use crate::enemies::warp_in::{spawn_warp_in, WarpInEnemy};
+use crate::enemies::Enemy;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::shared::{
+  random_f32_range, random_game_world_point_away_from_player, random_u16_range, swarm_drone_collision_groups, Category,
+  CollisionDamage, ImpactInfo, Substance, WrapAroundEntity, ORANGE,
+};
+use crate::shared_events::WaveEvent;
+use crate::timed_power_ups::WorldTime;
+use crate::toroidal;
+use bevy::app::{App, FixedUpdate, Plugin};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::{
+  ActiveEvents, AdditionalMassProperties, Ccd, Collider, GravityScale, RigidBody, Velocity,
+};
+use std::f32::consts::PI;
+use std::ops::Range;
+
+const SWARM_SIZE: Range<u16> = 8..21;
+const SPEED: f32 = 80.;
+const HEALTH: i16 = 1;
+const DAMAGE: u16 = 3;
+const SCORE: u16 = 15;
+const RADIUS: f32 = 4.;
+const NEIGHBOUR_RADIUS: f32 = 60.; // Distance within which drones of the same swarm flock together
+const COHESION_WEIGHT: f32 = 0.6;
+const ALIGNMENT_WEIGHT: f32 = 0.8;
+const TARGET_WEIGHT: f32 = 1.;
+const DIVE_DISTANCE: f32 = 120.; // Distance from player at which a drone stops flocking and dives at it
+const DIVE_SPEED_MULTIPLIER: f32 = 1.6;
+const BREAK_FORMATION_RATIO: f32 = 0.4; // Share of a swarm that must be alive for the drones to keep flocking
+const TURN_RATE: f32 = 4.; // How quickly drones adjust their velocity towards the desired velocity
+
+pub struct SwarmPlugin;
+
+impl Plugin for SwarmPlugin {
+  fn build(&self, app: &mut App) {
+    app.add_systems(FixedUpdate, swarm_movement_system.run_if(in_state(GameState::Playing)));
+  }
+}
+
+#[derive(Component)]
+struct Drone {
+  swarm_id: u32,
+  swarm_size: u16,
+}
+
+pub fn spawn_swarm_wave(event: &WaveEvent, commands: &mut Commands, asset_server: &Res<AssetServer>) {
+  for _ in 0..event.swarm_count {
+    let swarm_size = random_u16_range(SWARM_SIZE.start, SWARM_SIZE.end);
+    let centre = random_game_world_point_away_from_player(event.player_position, 350.);
+    spawn_warp_in(commands, asset_server, centre, WarpInEnemy::Swarm { swarm_size });
+  }
+}
+
+pub(crate) fn spawn_swarm(commands: &mut Commands, centre: Vec3, swarm_size: u16) {
+  let swarm_id = rand::random::<u32>();
+  for _ in 0..swarm_size {
+    let offset = Vec3::new(random_f32_range(-30., 30.), random_f32_range(-30., 30.), 0.);
+    spawn_drone(commands, toroidal::wrap_position(centre + offset), swarm_id, swarm_size);
+  }
+}
+
+fn spawn_drone(commands: &mut Commands, spawn_point: Vec3, swarm_id: u32, swarm_size: u16) {
+  let shape = shapes::Polygon {
+    points: vec![
+      Vec2::new(0., 5.),
+      Vec2::new(-4., -4.),
+      Vec2::new(0., -2.),
+      Vec2::new(4., -4.),
+    ],
+    closed: true,
+  };
+  commands
+    .spawn((
+      ShapeBundle {
+        path: GeometryBuilder::build_as(&shape),
+        spatial: SpatialBundle {
+          transform: Transform::from_translation(spawn_point),
+          ..default()
+        },
+        ..default()
+      },
+      Fill::color(ORANGE),
+      Name::new("Swarm Drone"),
+      RigidBody::Dynamic,
+      Collider::ball(RADIUS),
+      ActiveEvents::COLLISION_EVENTS,
+      ImpactInfo {
+        impact_category: Category::S,
+        death_category: Category::S,
+        substance: Substance::Metal,
+      },
+      GravityScale(0.),
+      Velocity::zero(),
+      AdditionalMassProperties::Mass(1.),
+      swarm_drone_collision_groups(),
+      Enemy {
+        health_points: HEALTH,
+        movement_speed: SPEED,
+        score_points: SCORE,
+      },
+      Drone { swarm_id, swarm_size },
+      Steering {
+        radius: RADIUS,
+        approach: Approach::Seek,
+      },
+      CollisionDamage::new(DAMAGE),
+    ))
+    .insert((Ccd::enabled(), WrapAroundEntity));
+}
+
+// Drones flock with the other drones of their swarm (cohesion, alignment and separation) while heading for the
+// player, dive at the player once close enough and stop flocking altogether once their swarm has been thinned out
+fn swarm_movement_system(
+  world_time: Res<WorldTime>,
+  mut drone_query: Query<(Entity, &mut Transform, &mut Velocity, &Enemy, &Drone, &Steering), Without<Player>>,
+  player_query: Query<&Transform, With<Player>>,
+  spatial_index: Res<SpatialIndex>,
+) {
+  let drones: Vec<(u32, Vec3, Vec2)> = drone_query
+    .iter()
+    .map(|(_, transform, velocity, _, drone, _)| (drone.swarm_id, transform.translation, velocity.linvel))
+    .collect();
+  let mut swarm_sizes: HashMap<u32, u16> = HashMap::new();
+  for (swarm_id, _, _) in drones.iter() {
+    *swarm_sizes.entry(*swarm_id).or_default() += 1;
+  }
+  let player_position = player_query.get_single().ok().map(|transform| transform.translation);
+
+  for (entity, mut transform, mut velocity, enemy, drone, steering) in drone_query.iter_mut() {
+    let position = transform.translation.truncate();
+    let player_delta = player_position.map(|player| toroidal::shortest_delta(transform.translation, player).truncate());
+    let is_in_formation =
+      swarm_sizes.get(&drone.swarm_id).copied().unwrap_or(0) as f32 >= drone.swarm_size as f32 * BREAK_FORMATION_RATIO;
+    let is_diving = player_delta.is_some_and(|delta| delta.length() < DIVE_DISTANCE);
+
+    let desired_velocity = match player_delta {
+      Some(delta) if is_diving || !is_in_formation => {
+        seek(position, position + delta, enemy.movement_speed * DIVE_SPEED_MULTIPLIER)
+      }
+      _ => {
+        let mut neighbour_positions = Vec::new();
+        let mut neighbour_velocities = Vec::new();
+        for (swarm_id, other_position, other_velocity) in drones.iter() {
+          let offset = toroidal::shortest_delta(transform.translation, *other_position).truncate();
+          if *swarm_id == drone.swarm_id && offset != Vec2::ZERO && offset.length() < NEIGHBOUR_RADIUS {
+            neighbour_positions.push(position + offset);
+            neighbour_velocities.push(*other_velocity);
+          }
+        }
+        let to_player = player_delta.map_or(Vec2::ZERO, |delta| delta.normalize_or_zero());
+        let heading = cohesion(position, &neighbour_positions) * COHESION_WEIGHT
+          + alignment(&neighbour_velocities) * ALIGNMENT_WEIGHT
+          + to_player * TARGET_WEIGHT;
+        heading.normalize_or_zero() * enemy.movement_speed
+      }
+    };
+
+    let max_speed = enemy.movement_speed * DIVE_SPEED_MULTIPLIER;
+    let desired_velocity = steer(entity, steering, position, desired_velocity, max_speed, &spatial_index);
+    velocity.linvel = velocity
+      .linvel
+      .lerp(desired_velocity, (TURN_RATE * world_time.delta_seconds()).min(1.));
+    velocity.angvel = 0.;
+    if velocity.linvel.length_squared() > f32::EPSILON {
+      transform.rotation = Quat::from_rotation_z(velocity.linvel.y.atan2(velocity.linvel.x) - PI / 2.);
+    }
+  }
+}
@@ -3,25 +3,38 @@ use bevy::prelude::*;
 use bevy_rapier2d::dynamics::{AdditionalMassProperties, Ccd, GravityScale, RigidBody, Velocity};
 use bevy_rapier2d::geometry::{ActiveEvents, Collider};
 
-use crate::enemies::{move_toward_target, Enemy};
+use crate::difficulty::Difficulty;
+use crate::enemies::firing_pattern::{Emitter, FiringPattern, Pattern};
+use crate::enemies::steering::{Approach, SpatialIndex, Steering};
+use crate::enemies::warp_in::{spawn_warp_in, WarpInEnemy};
+use crate::enemies::{calculate_lead_direction, move_toward_target, Enemy};
 use crate::game_state::GameState;
 use crate::player::Player;
 use crate::shared::{
   default_enemy_collision_groups, enemy_projectile_collision_groups, random_f32_range,
-  random_game_world_point_away_from_player, Category, CollisionDamage, EntityType, ImpactInfo, ProjectileInfo,
-  Substance, RED,
+  random_game_world_point_away_from_player, Category, CollisionDamage, EntityType, HomingInfo, ImpactInfo,
+  ProjectileInfo, Substance, WrapAroundEntity, RED,
 };
 use crate::shared_events::{ProjectileSpawnEvent, WaveEvent};
+use crate::timed_power_ups::WorldTime;
+use std::f32::consts::{PI, TAU};
 
 const SMALL_UFO_SPEED: f32 = 50.;
 const LARGE_UFO_SPEED: f32 = 35.;
 const SMALL_UFO_SHOOTING_COOLDOWN: f32 = 1.;
 const LARGE_UFO_SHOOTING_COOLDOWN: f32 = 0.2;
+const LARGE_UFO_MISSILE_COOLDOWN: f32 = 5.;
 const SMALL_UFO_HEALTH: i16 = 15;
 const LARGE_UFO_HEALTH: i16 = 90;
 const DAMAGE: u16 = 5;
 const SMALL_UFO_SCORE: u16 = 75;
 const LARGE_UFO_SCORE: u16 = 150;
+const SMALL_UFO_ACCURACY: f32 = 0.25; // 0 aims at the player's current position, 1 leads the target perfectly
+const LARGE_UFO_ACCURACY: f32 = 0.1;
+const ACCURACY_GAIN_PER_WAVE: f32 = 0.05;
+const MAX_ACCURACY: f32 = 0.9;
+const SMALL_UFO_SPREAD_WAVE: u16 = 6; // Wave from which small UFOs fire a spread instead of single shots
+const LARGE_UFO_SPIRAL_WAVE: u16 = 8; // Wave from which large UFOs fire a spiral instead of two opposing shots
 
 pub struct UfoPlugin;
 
@@ -35,8 +48,9 @@ impl Plugin for UfoPlugin {
 
 #[derive(Component)]
 struct Ufo {
-  shooting_cooldown: f32,
+  missile_cooldown: f32,
   size: UfoSize,
+  accuracy: f32,
 }
 
 enum UfoSize {
@@ -44,214 +58,358 @@ enum UfoSize {
   Large,
 }
 
-pub fn spawn_ufo_wave(event: &WaveEvent, mut commands: &mut Commands, asset_server: &Res<AssetServer>) {
+pub fn spawn_ufo_wave(
+  event: &WaveEvent,
+  commands: &mut Commands,
+  asset_server: &Res<AssetServer>,
+  difficulty: &Difficulty,
+) {
   for _ in 0..event.large_ufo_count {
     let spawn_point = random_game_world_point_away_from_player(event.player_position, 300.);
-    spawn_large_ufo(&mut commands, &asset_server, spawn_point);
-    info!("Spawn: Large UFO at {:?}", spawn_point);
+    let accuracy = accuracy_for_wave(
+      LARGE_UFO_ACCURACY,
+      event.wave,
+      difficulty,
+      event.enemy_accuracy_modifier,
+    );
+    spawn_warp_in(
+      commands,
+      asset_server,
+      spawn_point,
+      WarpInEnemy::LargeUfo {
+        accuracy,
+        wave: event.wave,
+      },
+    );
   }
   for _ in 0..event.small_ufo_count {
     let spawn_point = random_game_world_point_away_from_player(event.player_position, 200.);
-    spawn_small_ufo(&mut commands, &asset_server, spawn_point);
-    info!("Spawn: Small UFO at {:?}", spawn_point);
+    let accuracy = accuracy_for_wave(
+      SMALL_UFO_ACCURACY,
+      event.wave,
+      difficulty,
+      event.enemy_accuracy_modifier,
+    );
+    spawn_warp_in(
+      commands,
+      asset_server,
+      spawn_point,
+      WarpInEnemy::SmallUfo {
+        accuracy,
+        wave: event.wave,
+      },
+    );
   }
 }
 
-fn spawn_small_ufo(commands: &mut &mut Commands, asset_server: &Res<AssetServer>, spawn_point: Vec3) {
-  commands.spawn((
-    SpriteBundle {
-      texture: asset_server.load("sprites/enemy_ufo_small.png"),
-      transform: Transform::from_translation(spawn_point),
-      ..default()
-    },
-    Name::new("UFO Small"),
-    RigidBody::Dynamic,
-    Collider::ball(9.),
-    ActiveEvents::COLLISION_EVENTS,
-    ImpactInfo {
-      impact_category: Category::S,
-      death_category: Category::M,
-      substance: Substance::Metal,
-    },
-    GravityScale(0.),
-    Velocity {
-      linvel: Vec2::new(0., 0.),
-      angvel: 1.,
-    },
-    AdditionalMassProperties::Mass(4.),
-    default_enemy_collision_groups(),
-    Ccd::enabled(),
-    Enemy {
-      health_points: SMALL_UFO_HEALTH,
-      movement_speed: SMALL_UFO_SPEED,
-      score_points: SMALL_UFO_SCORE,
-    },
-    Ufo {
-      shooting_cooldown: SMALL_UFO_SHOOTING_COOLDOWN,
-      size: UfoSize::Small,
+// Enemies become better at leading their shots as the waves progress so that early waves stay forgiving
+fn accuracy_for_wave(base_accuracy: f32, wave: u16, difficulty: &Difficulty, modifier: f32) -> f32 {
+  let accuracy = (base_accuracy + wave.saturating_sub(1) as f32 * ACCURACY_GAIN_PER_WAVE) * difficulty.enemy_accuracy();
+  (accuracy + modifier).clamp(0., MAX_ACCURACY)
+}
+
+fn small_ufo_firing_pattern(wave: u16, accuracy: f32) -> FiringPattern {
+  FiringPattern {
+    pattern: if wave >= SMALL_UFO_SPREAD_WAVE {
+      Pattern::Spread { count: 3, angle: 0.5 }
+    } else {
+      Pattern::AimedBurst { count: 1 }
     },
-    CollisionDamage::new(DAMAGE),
-  ));
+    projectile: enemy_projectile_info(100., 3.5, SMALL_UFO_SHOOTING_COOLDOWN),
+    cooldown: SMALL_UFO_SHOOTING_COOLDOWN,
+    shot_delay: 0.,
+    rotation: 0.,
+    spawn_distance: 15.,
+    accuracy,
+  }
+}
+
+fn large_ufo_firing_pattern(wave: u16) -> FiringPattern {
+  if wave >= LARGE_UFO_SPIRAL_WAVE {
+    FiringPattern {
+      pattern: Pattern::Spiral {
+        count: 12,
+        angle_per_shot: TAU / 12.,
+      },
+      projectile: enemy_projectile_info(75., 4., LARGE_UFO_SHOOTING_COOLDOWN * 8.),
+      cooldown: LARGE_UFO_SHOOTING_COOLDOWN * 8.,
+      shot_delay: 0.05,
+      rotation: 0.2,
+      spawn_distance: 25.,
+      accuracy: 0.,
+    }
+  } else {
+    FiringPattern {
+      pattern: Pattern::Ring { count: 2 },
+      projectile: enemy_projectile_info(75., 4., LARGE_UFO_SHOOTING_COOLDOWN),
+      cooldown: LARGE_UFO_SHOOTING_COOLDOWN,
+      shot_delay: 0.,
+      rotation: 0.,
+      spawn_distance: 25.,
+      accuracy: 0.,
+    }
+  }
 }
 
-fn spawn_large_ufo(commands: &mut &mut Commands, asset_server: &Res<AssetServer>, spawn_point: Vec3) {
-  commands.spawn((
-    SpriteBundle {
-      texture: asset_server.load("sprites/enemy_ufo_large.png"),
-      transform: Transform::from_translation(spawn_point),
+fn enemy_projectile_info(speed: f32, max_life_time: f32, cooldown: f32) -> ProjectileInfo {
+  ProjectileInfo {
+    damage: DAMAGE,
+    speed,
+    max_life_time,
+    cooldown,
+    collider: Collider::cuboid(1.25, 1.25),
+    collision_groups: enemy_projectile_collision_groups(),
+    sprite: Sprite {
+      color: RED,
+      custom_size: Some(Vec2::new(2.5, 2.5)),
       ..default()
     },
-    Name::new("UFO Large"),
-    RigidBody::Dynamic,
-    Collider::ball(17.),
-    ActiveEvents::COLLISION_EVENTS,
-    ImpactInfo {
-      impact_category: Category::M,
-      death_category: Category::L,
-      substance: Substance::Metal,
-    },
-    GravityScale(0.),
-    Velocity {
-      linvel: Vec2::new(0., 0.),
-      angvel: 0.6,
-    },
-    AdditionalMassProperties::Mass(14.),
-    default_enemy_collision_groups(),
-    Ccd::enabled(),
-    Enemy {
-      health_points: LARGE_UFO_HEALTH,
-      movement_speed: LARGE_UFO_SPEED,
-      score_points: LARGE_UFO_SCORE,
-    },
-    Ufo {
-      shooting_cooldown: LARGE_UFO_SHOOTING_COOLDOWN,
-      size: UfoSize::Large,
-    },
-    CollisionDamage::new(DAMAGE),
-  ));
+    by: EntityType::Enemy,
+    homing: None,
+    pierce: 0,
+    ricochet: 0,
+  }
+}
+
+pub(crate) fn spawn_small_ufo(
+  commands: &mut Commands,
+  asset_server: &Res<AssetServer>,
+  spawn_point: Vec3,
+  accuracy: f32,
+  wave: u16,
+) {
+  commands
+    .spawn((
+      SpriteBundle {
+        texture: asset_server.load("sprites/enemy_ufo_small.png"),
+        transform: Transform::from_translation(spawn_point),
+        ..default()
+      },
+      Name::new("UFO Small"),
+      RigidBody::Dynamic,
+      Collider::ball(9.),
+      ActiveEvents::COLLISION_EVENTS,
+      ImpactInfo {
+        impact_category: Category::S,
+        death_category: Category::M,
+        substance: Substance::Metal,
+      },
+      GravityScale(0.),
+      Velocity {
+        linvel: Vec2::new(0., 0.),
+        angvel: 1.,
+      },
+      AdditionalMassProperties::Mass(4.),
+      default_enemy_collision_groups(),
+      Ccd::enabled(),
+      Enemy {
+        health_points: SMALL_UFO_HEALTH,
+        movement_speed: SMALL_UFO_SPEED,
+        score_points: SMALL_UFO_SCORE,
+      },
+      Ufo {
+        missile_cooldown: 0.,
+        size: UfoSize::Small,
+        accuracy,
+      },
+      Steering {
+        radius: 9.,
+        approach: Approach::Seek,
+      },
+      CollisionDamage::new(DAMAGE),
+    ))
+    .insert((WrapAroundEntity, Emitter::new(small_ufo_firing_pattern(wave, accuracy))));
+}
+
+pub(crate) fn spawn_large_ufo(
+  commands: &mut Commands,
+  asset_server: &Res<AssetServer>,
+  spawn_point: Vec3,
+  accuracy: f32,
+  wave: u16,
+) {
+  commands
+    .spawn((
+      SpriteBundle {
+        texture: asset_server.load("sprites/enemy_ufo_large.png"),
+        transform: Transform::from_translation(spawn_point),
+        ..default()
+      },
+      Name::new("UFO Large"),
+      RigidBody::Dynamic,
+      Collider::ball(17.),
+      ActiveEvents::COLLISION_EVENTS,
+      ImpactInfo {
+        impact_category: Category::M,
+        death_category: Category::L,
+        substance: Substance::Metal,
+      },
+      GravityScale(0.),
+      Velocity {
+        linvel: Vec2::new(0., 0.),
+        angvel: 0.6,
+      },
+      AdditionalMassProperties::Mass(14.),
+      default_enemy_collision_groups(),
+      Ccd::enabled(),
+      Enemy {
+        health_points: LARGE_UFO_HEALTH,
+        movement_speed: LARGE_UFO_SPEED,
+        score_points: LARGE_UFO_SCORE,
+      },
+      Ufo {
+        missile_cooldown: LARGE_UFO_MISSILE_COOLDOWN,
+        size: UfoSize::Large,
+        accuracy,
+      },
+      Steering {
+        radius: 17.,
+        approach: Approach::Arrive { slowing_radius: 120. },
+      },
+      CollisionDamage::new(DAMAGE),
+    ))
+    .insert((WrapAroundEntity, Emitter::new(large_ufo_firing_pattern(wave))));
 }
 
 fn ufo_movement_system(
-  mut ufo_query: Query<(&Transform, &mut Velocity, &Enemy), With<Ufo>>,
+  mut ufo_query: Query<(Entity, &Transform, &mut Velocity, &Enemy, &Steering), With<Ufo>>,
   player_query: Query<(Entity, &Transform), With<Player>>,
+  spatial_index: Res<SpatialIndex>,
 ) {
-  for (transform, mut velocity, enemy) in ufo_query.iter_mut() {
+  for (entity, transform, mut velocity, enemy, steering) in ufo_query.iter_mut() {
     if let Ok(player) = player_query.get_single().as_ref() {
-      move_toward_target(&player.1, transform, &mut *velocity, enemy.movement_speed);
+      move_toward_target(
+        entity,
+        &player.1,
+        transform,
+        &mut *velocity,
+        enemy.movement_speed,
+        steering,
+        &spatial_index,
+      );
     }
   }
 }
 
 fn ufo_shooting_system(
-  time: Res<Time>,
+  world_time: Res<WorldTime>,
   mut query: Query<(&mut Ufo, &Transform)>,
   mut projective_spawn_event: EventWriter<ProjectileSpawnEvent>,
-  player_query: Query<&Transform, With<Player>>,
+  player_query: Query<(&Transform, &Velocity), With<Player>>,
 ) {
   for (mut ufo, transform) in query.iter_mut() {
-    // Shoot a projectile if the cooldown is over
-    match ufo.size {
-      UfoSize::Small => small_ufo_shooting(&mut projective_spawn_event, &player_query, &mut ufo, transform),
-      UfoSize::Large => large_ufo_shooting(&mut projective_spawn_event, &mut ufo, transform),
-    };
+    // Fire a missile if the cooldown is over
+    if let UfoSize::Large = ufo.size {
+      large_ufo_missile_shooting(&mut projective_spawn_event, &player_query, &mut ufo, transform);
+    }
 
-    // Update the shooting cooldown
-    if ufo.shooting_cooldown > 0. {
-      ufo.shooting_cooldown -= time.delta_seconds();
+    // Update the missile cooldown
+    if ufo.missile_cooldown > 0. {
+      ufo.missile_cooldown -= world_time.delta_seconds();
     }
   }
 }
 
-fn small_ufo_shooting(
+fn large_ufo_missile_shooting(
   projective_spawn_event: &mut EventWriter<ProjectileSpawnEvent>,
-  player_query: &Query<&Transform, With<Player>>,
+  player_query: &Query<(&Transform, &Velocity), With<Player>>,
   ufo: &mut Mut<Ufo>,
   transform: &Transform,
 ) {
-  // Shoot a projectile if the cooldown is over
-  if ufo.shooting_cooldown <= 0. {
-    let origin_forward = get_origin_forward(&player_query, transform);
+  if ufo.missile_cooldown <= 0. {
     let info = ProjectileInfo {
-      damage: DAMAGE,
-      speed: 100.,
-      max_life_time: 3.5,
-      cooldown: SMALL_UFO_SHOOTING_COOLDOWN,
-      collider: Collider::cuboid(1.25, 1.25),
+      damage: DAMAGE * 2,
+      speed: 90.,
+      max_life_time: 6.,
+      cooldown: LARGE_UFO_MISSILE_COOLDOWN,
+      collider: Collider::cuboid(1.5, 3.),
       collision_groups: enemy_projectile_collision_groups(),
       sprite: Sprite {
         color: RED,
-        custom_size: Some(Vec2::new(2.5, 2.5)),
+        custom_size: Some(Vec2::new(3., 6.)),
         ..default()
       },
       by: EntityType::Enemy,
+      homing: Some(HomingInfo {
+        turn_rate: 1.2,
+        acquisition_cone: PI,
+        acquisition_range: 600.,
+        fuel: 4.,
+      }),
+      pierce: 0,
+      ricochet: 0,
     };
-    ufo.shooting_cooldown = info.cooldown;
+    let origin_forward = get_origin_forward(player_query, transform, info.speed, ufo.accuracy);
+    ufo.missile_cooldown = info.cooldown;
     projective_spawn_event.send(ProjectileSpawnEvent {
       projectile_info: info,
       origin_rotation: transform.rotation,
       origin_forward,
-      spawn_position: transform.translation + origin_forward * 15.,
+      spawn_position: transform.translation + origin_forward * 25.,
     });
   }
 }
 
-fn large_ufo_shooting(
-  mut projective_spawn_event: &mut EventWriter<ProjectileSpawnEvent>,
-  ufo: &mut Mut<Ufo>,
+fn get_origin_forward(
+  player_query: &Query<(&Transform, &Velocity), With<Player>>,
   transform: &Transform,
-) {
-  if ufo.shooting_cooldown <= 0. {
-    let info = ProjectileInfo {
-      damage: DAMAGE,
-      speed: 75.,
-      max_life_time: 4.,
-      cooldown: LARGE_UFO_SHOOTING_COOLDOWN,
-      collider: Collider::cuboid(1.25, 1.25),
-      collision_groups: enemy_projectile_collision_groups(),
-      sprite: Sprite {
-        color: RED,
-        custom_size: Some(Vec2::new(2.5, 2.5)),
-        ..default()
-      },
-      by: EntityType::Enemy,
-    };
-    ufo.shooting_cooldown = info.cooldown;
-    send_projectile_spawn_event(
-      &mut projective_spawn_event,
-      transform,
-      info.clone(),
-      transform.rotation * Vec3::Y,
-    );
-    send_projectile_spawn_event(
-      &mut projective_spawn_event,
-      transform,
-      info,
-      transform.rotation * -Vec3::Y,
+  projectile_speed: f32,
+  accuracy: f32,
+) -> Vec3 {
+  if let Ok((player_transform, player_velocity)) = player_query.get_single() {
+    return calculate_lead_direction(
+      transform.translation,
+      player_transform.translation,
+      player_velocity.linvel,
+      projectile_speed,
+      accuracy,
     );
   }
+  let random_number = random_f32_range(-1., 1.);
+  let anchor = if random_number > 0. { 1. } else { -1. };
+  Vec3::new(random_number, anchor - random_number, 0.)
 }
 
-fn send_projectile_spawn_event(
-  projective_spawn_event: &mut EventWriter<ProjectileSpawnEvent>,
-  transform: &Transform,
-  projectile_info: ProjectileInfo,
-  origin_forward: Vec3,
-) {
-  projective_spawn_event.send(ProjectileSpawnEvent {
-    projectile_info,
-    origin_rotation: transform.rotation,
-    origin_forward,
-    spawn_position: transform.translation + origin_forward * 25.,
-  });
-}
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::assert_approx_eq;
+
+  #[test]
+  fn accuracy_starts_at_the_base_accuracy_of_the_archetype() {
+    assert_approx_eq(
+      accuracy_for_wave(SMALL_UFO_ACCURACY, 1, &Difficulty::Normal, 0.),
+      SMALL_UFO_ACCURACY,
+    );
+    assert_approx_eq(
+      accuracy_for_wave(LARGE_UFO_ACCURACY, 1, &Difficulty::Normal, 0.),
+      LARGE_UFO_ACCURACY,
+    );
+  }
 
-fn get_origin_forward(player_query: &Query<&Transform, With<Player>>, transform: &Transform) -> Vec3 {
-  if let Ok(player) = player_query.get_single().as_ref() {
-    let direction = player.translation - transform.translation;
-    let direction = direction / direction.length();
-    return direction;
+  #[test]
+  fn accuracy_rises_with_the_wave_and_difficulty() {
+    let accuracy = |wave: u16, difficulty: Difficulty| accuracy_for_wave(SMALL_UFO_ACCURACY, wave, &difficulty, 0.);
+    assert_approx_eq(
+      accuracy(3, Difficulty::Normal),
+      SMALL_UFO_ACCURACY + 2. * ACCURACY_GAIN_PER_WAVE,
+    );
+    assert_approx_eq(accuracy(1, Difficulty::Easy), SMALL_UFO_ACCURACY * 0.5);
+    assert!(accuracy(5, Difficulty::Easy) < accuracy(5, Difficulty::Normal));
+    assert!(accuracy(5, Difficulty::Normal) < accuracy(5, Difficulty::Hard));
+    assert!(accuracy(5, Difficulty::Hard) < accuracy(5, Difficulty::Nightmare));
+  }
+
+  #[test]
+  fn accuracy_modifier_is_applied_within_the_limits() {
+    assert_approx_eq(
+      accuracy_for_wave(SMALL_UFO_ACCURACY, 1, &Difficulty::Normal, 0.1),
+      SMALL_UFO_ACCURACY + 0.1,
+    );
+    assert_approx_eq(accuracy_for_wave(LARGE_UFO_ACCURACY, 1, &Difficulty::Easy, -1.), 0.);
+    assert_approx_eq(
+      accuracy_for_wave(SMALL_UFO_ACCURACY, 50, &Difficulty::Nightmare, 1.),
+      MAX_ACCURACY,
+    );
   }
-  let random_number = random_f32_range(-1., 1.);
-  let anchor = if random_number > 0. { 1. } else { -1. };
-  Vec3::new(random_number, anchor - random_number, 0.)
 }
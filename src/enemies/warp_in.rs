@@ -0,0 +1,148 @@
+use crate::camera::PIXEL_PERFECT_BLOOM_LAYER;
+use crate::enemies::{boss_morph, mine_layer, swarm, ufo};
+use crate::game_state::GameState;
+use crate::shared::{WrapAroundEntity, RED};
+use crate::shared_events::NextWaveEvent;
+use crate::timed_power_ups::WorldTime;
+use bevy::app::{App, Plugin, Update};
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+const WARP_IN_DURATION: f32 = 1.5;
+const START_SCALE: f32 = 3.; // The ring shrinks from this scale down to the size of the enemy
+const ROTATION_SPEED: f32 = 4.;
+
+pub struct WarpInPlugin;
+
+impl Plugin for WarpInPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(OnEnter(GameState::Starting), reset_warp_ins_system)
+      .add_systems(
+        Update,
+        (warp_in_system, next_wave_event).run_if(in_state(GameState::Playing)),
+      );
+  }
+}
+
+// The enemy that will be spawned once the warp-in has completed
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum WarpInEnemy {
+  SmallUfo { accuracy: f32, wave: u16 },
+  LargeUfo { accuracy: f32, wave: u16 },
+  MorphBoss,
+  Swarm { swarm_size: u16 },
+  MineLayer,
+}
+
+impl WarpInEnemy {
+  fn radius(&self) -> f32 {
+    match self {
+      WarpInEnemy::SmallUfo { .. } => 12.,
+      WarpInEnemy::LargeUfo { .. } => 22.,
+      WarpInEnemy::MorphBoss => 32.,
+      WarpInEnemy::Swarm { .. } => 40.,
+      WarpInEnemy::MineLayer => 16.,
+    }
+  }
+}
+
+#[derive(Component)]
+pub(crate) struct WarpIn {
+  enemy: WarpInEnemy,
+  timer: Timer,
+}
+
+// Telegraphs the arrival of an enemy with a shrinking ring and a sound cue - the enemy itself only exists once the
+// warp-in has completed, so it can neither act nor be hit before then
+pub(crate) fn spawn_warp_in(
+  commands: &mut Commands,
+  asset_server: &Res<AssetServer>,
+  spawn_point: Vec3,
+  enemy: WarpInEnemy,
+) {
+  let shape = shapes::Circle {
+    radius: enemy.radius(),
+    center: Vec2::ZERO,
+  };
+  commands.spawn((
+    ShapeBundle {
+      path: GeometryBuilder::build_as(&shape),
+      spatial: SpatialBundle {
+        transform: Transform::from_translation(spawn_point).with_scale(Vec3::splat(START_SCALE)),
+        ..default()
+      },
+      ..default()
+    },
+    Stroke::new(RED, 1.),
+    Name::new("Warp In"),
+    WarpIn {
+      enemy,
+      timer: Timer::from_seconds(WARP_IN_DURATION, TimerMode::Once),
+    },
+    PIXEL_PERFECT_BLOOM_LAYER,
+    WrapAroundEntity,
+    AudioBundle {
+      source: asset_server.load("audio/whoosh.ogg"),
+      settings: PlaybackSettings {
+        mode: bevy::audio::PlaybackMode::Remove,
+        volume: Volume::new(0.6),
+        spatial: true,
+        ..Default::default()
+      },
+    },
+  ));
+  debug!("Warp in: {:?} at {:?}", enemy, spawn_point);
+}
+
+fn warp_in_system(
+  mut commands: Commands,
+  world_time: Res<WorldTime>,
+  asset_server: Res<AssetServer>,
+  mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+  mut query: Query<(Entity, &mut WarpIn, &mut Transform)>,
+) {
+  for (entity, mut warp_in, mut transform) in query.iter_mut() {
+    warp_in.timer.tick(world_time.delta());
+    let remaining = 1. - warp_in.timer.fraction();
+    transform.scale = Vec3::splat(1. + (START_SCALE - 1.) * remaining);
+    transform.rotate_z(ROTATION_SPEED * world_time.delta_seconds());
+    if !warp_in.timer.finished() {
+      continue;
+    }
+
+    commands.entity(entity).despawn();
+    let spawn_point = transform.translation;
+    match warp_in.enemy {
+      WarpInEnemy::SmallUfo { accuracy, wave } => {
+        ufo::spawn_small_ufo(&mut commands, &asset_server, spawn_point, accuracy, wave)
+      }
+      WarpInEnemy::LargeUfo { accuracy, wave } => {
+        ufo::spawn_large_ufo(&mut commands, &asset_server, spawn_point, accuracy, wave)
+      }
+      WarpInEnemy::MorphBoss => {
+        boss_morph::spawn_morph_boss(&mut commands, &asset_server, spawn_point, &mut texture_atlas_layouts)
+      }
+      WarpInEnemy::Swarm { swarm_size } => swarm::spawn_swarm(&mut commands, spawn_point, swarm_size),
+      WarpInEnemy::MineLayer => mine_layer::spawn_mine_layer(&mut commands, spawn_point),
+    }
+    info!("Spawn: {:?} at {:?}", warp_in.enemy, spawn_point);
+  }
+}
+
+fn reset_warp_ins_system(mut commands: Commands, query: Query<Entity, With<WarpIn>>) {
+  for entity in query.iter() {
+    commands.entity(entity).despawn();
+  }
+}
+
+fn next_wave_event(
+  mut next_wave_events: EventReader<NextWaveEvent>,
+  commands: Commands,
+  query: Query<Entity, With<WarpIn>>,
+) {
+  if next_wave_events.read().next().is_some() {
+    reset_warp_ins_system(commands, query);
+  }
+}
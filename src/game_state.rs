@@ -26,6 +26,7 @@ pub enum GameState {
   Starting,
   Playing,
   Paused,
+  ChoosingPowerUp,
   Dead,
 }
 
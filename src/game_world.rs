@@ -1,12 +1,17 @@
+use crate::camera::InGameCameras;
 use crate::shared::{WrapAroundEntity, DEFAULT_FONT, VERY_DARK_1, VERY_DARK_2};
+use crate::toroidal;
 use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
 use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::transform::TransformSystem;
+use bevy_prototype_lyon::prelude::{Fill, Path, ShapeBundle, Stroke};
 use bevy_rapier2d::dynamics::RigidBody;
 
 pub(crate) const WORLD_SIZE: f32 = 1000.;
 const TILES: f32 = 5.; // Must result in a whole number when dividing by WORLD_SIZE
 const MARGIN: f32 = 2.; // Must be divisible by 2
-const WRAPAROUND_MARGIN: f32 = 25.;
+const SURROUNDING_TILES: i32 = 2; // Tiles repeated beyond each edge so that the camera never looks into the void
 
 pub struct GameWorldPlugin;
 
@@ -14,10 +19,22 @@ impl Plugin for GameWorldPlugin {
   fn build(&self, app: &mut App) {
     app
       .add_systems(Startup, create_world_system)
-      .add_systems(FixedUpdate, wraparound_system);
+      .add_systems(FixedUpdate, wraparound_system)
+      .add_systems(Update, spawn_ghost_system)
+      .add_systems(
+        PostUpdate,
+        update_ghosts_system.before(TransformSystem::TransformPropagate),
+      );
   }
 }
 
+// A purely visual copy of a wraparound entity, shown on the opposite side of the world whenever the camera looks
+// across an edge of the world at the entity
+#[derive(Component)]
+struct Ghost {
+  source: Entity,
+}
+
 fn create_world_system(
   mut commands: Commands,
   mut meshes: ResMut<Assets<Mesh>>,
@@ -29,13 +46,13 @@ fn create_world_system(
   let half_margin = MARGIN / 2.;
   let adjusted_tile_size = tile_size - MARGIN;
 
-  for i in 0..TILES as i32 {
-    for j in (0..TILES as i32).rev() {
+  for i in -SURROUNDING_TILES..TILES as i32 + SURROUNDING_TILES {
+    for j in (-SURROUNDING_TILES..TILES as i32 + SURROUNDING_TILES).rev() {
       let x = (i as f32 * tile_size) - half_world + (tile_size / 2.);
       let y = (j as f32 * tile_size) - half_world + (tile_size / 2.);
 
-      let letter = (b'A' + i as u8) as char;
-      let description = format!("{}{}", letter, TILES as i32 - j);
+      let letter = (b'A' + i.rem_euclid(TILES as i32) as u8) as char;
+      let description = format!("{}{}", letter, TILES as i32 - j.rem_euclid(TILES as i32));
 
       commands
         .spawn((
@@ -69,17 +86,118 @@ fn create_world_system(
 }
 
 pub(crate) fn wraparound_system(mut entities: Query<&mut Transform, (With<RigidBody>, With<WrapAroundEntity>)>) {
-  let extents = Vec3::new(WORLD_SIZE / 2., WORLD_SIZE / 2., 0.);
   for mut transform in entities.iter_mut() {
-    if transform.translation.x > (extents.x + WRAPAROUND_MARGIN) {
-      transform.translation.x = -extents.x - WRAPAROUND_MARGIN;
-    } else if transform.translation.x < (-extents.x - WRAPAROUND_MARGIN) {
-      transform.translation.x = extents.x + WRAPAROUND_MARGIN;
+    let wrapped_position = toroidal::wrap_position(transform.translation);
+    if wrapped_position != transform.translation {
+      transform.translation = wrapped_position;
+    }
+  }
+}
+
+fn spawn_ghost_system(
+  mut commands: Commands,
+  query: Query<
+    (
+      Entity,
+      Option<&Sprite>,
+      Option<&Handle<Image>>,
+      Option<&TextureAtlas>,
+      Option<&Path>,
+      Option<&Stroke>,
+      Option<&Fill>,
+      Option<&RenderLayers>,
+    ),
+    Added<WrapAroundEntity>,
+  >,
+) {
+  for (entity, sprite, texture, atlas, path, stroke, fill, render_layers) in query.iter() {
+    let mut ghost = if let Some(path) = path {
+      commands.spawn(ShapeBundle {
+        path: path.clone(),
+        spatial: SpatialBundle::HIDDEN_IDENTITY,
+        ..default()
+      })
+    } else if let (Some(sprite), Some(texture)) = (sprite, texture) {
+      commands.spawn(SpriteBundle {
+        sprite: sprite.clone(),
+        texture: texture.clone(),
+        visibility: Visibility::Hidden,
+        ..default()
+      })
+    } else {
+      continue;
+    };
+    ghost.insert((Ghost { source: entity }, Name::new("Ghost")));
+    if let Some(atlas) = atlas {
+      ghost.insert(atlas.clone());
+    }
+    if let Some(stroke) = stroke {
+      ghost.insert(*stroke);
+    }
+    if let Some(fill) = fill {
+      ghost.insert(*fill);
+    }
+    if let Some(render_layers) = render_layers {
+      ghost.insert(render_layers.clone());
+    }
+  }
+}
+
+fn update_ghosts_system(
+  mut commands: Commands,
+  camera_query: Query<&Transform, (With<InGameCameras>, Without<Ghost>)>,
+  source_query: Query<
+    (&Transform, &Visibility, Option<&Handle<Image>>, Option<&TextureAtlas>),
+    (With<WrapAroundEntity>, Without<Ghost>),
+  >,
+  mut ghost_query: Query<
+    (
+      Entity,
+      &Ghost,
+      &mut Transform,
+      &mut Visibility,
+      Option<&mut Handle<Image>>,
+      Option<&mut TextureAtlas>,
+    ),
+    Without<WrapAroundEntity>,
+  >,
+) {
+  let Some(camera) = camera_query.iter().next() else {
+    return;
+  };
+  for (ghost_entity, ghost, mut transform, mut visibility, texture, atlas) in ghost_query.iter_mut() {
+    let Ok((source_transform, source_visibility, source_texture, source_atlas)) = source_query.get(ghost.source) else {
+      commands.entity(ghost_entity).despawn();
+      continue;
+    };
+
+    // Only show the ghost if the source is visible and closer to the camera when looking across an edge of the world
+    let closest_position =
+      camera.translation + toroidal::shortest_delta(camera.translation, source_transform.translation);
+    let is_across_edge = (closest_position.truncate() - source_transform.translation.truncate()).length() > 1.;
+    let new_visibility = if is_across_edge && *source_visibility != Visibility::Hidden {
+      Visibility::Visible
+    } else {
+      Visibility::Hidden
+    };
+    if *visibility != new_visibility {
+      *visibility = new_visibility;
+    }
+    if new_visibility == Visibility::Hidden {
+      continue;
+    }
+
+    *transform = Transform {
+      translation: closest_position.truncate().extend(source_transform.translation.z),
+      ..*source_transform
+    };
+    if let (Some(mut texture), Some(source_texture)) = (texture, source_texture) {
+      if *texture != *source_texture {
+        *texture = source_texture.clone();
+      }
     }
-    if transform.translation.y > (extents.y + WRAPAROUND_MARGIN) {
-      transform.translation.y = -extents.y - WRAPAROUND_MARGIN;
-    } else if transform.translation.y < (-extents.y - WRAPAROUND_MARGIN) {
-      transform.translation.y = extents.y + WRAPAROUND_MARGIN;
+    if let (Some(mut atlas), Some(source_atlas)) = (atlas, source_atlas) {
+      atlas.index = source_atlas.index;
     }
   }
 }
@@ -1,3 +1,4 @@
+use crate::difficulty::{record_high_score_system, Difficulty, HighScores};
 use crate::game_state::GameState;
 use crate::in_game_ui::UiComponent;
 use crate::shared_resources::Score;
@@ -10,7 +11,10 @@ impl Plugin for GameOverMenuPlugin {
   fn build(&self, app: &mut App) {
     app
       .add_systems(OnEnter(GameState::Starting), hide_game_over_ui_system)
-      .add_systems(OnEnter(GameState::Dead), show_game_over_ui_system);
+      .add_systems(
+        OnEnter(GameState::Dead),
+        show_game_over_ui_system.after(record_high_score_system),
+      );
   }
 }
 
@@ -19,7 +23,15 @@ struct GameOverUi;
 
 impl UiComponent for GameOverUi {}
 
-fn show_game_over_ui_system(mut commands: Commands, score: ResMut<Score>) {
+fn show_game_over_ui_system(
+  mut commands: Commands,
+  score: ResMut<Score>,
+  difficulty: Res<Difficulty>,
+  high_scores: Res<HighScores>,
+) {
+  let best = high_scores
+    .best(*difficulty)
+    .map_or(score.0, |high_score| high_score.score);
   commands
     .spawn((
       crate::in_game_ui::centered_overlay_base_ui(GameOverUi),
@@ -34,12 +46,23 @@ fn show_game_over_ui_system(mut commands: Commands, score: ResMut<Score>) {
         },
       ));
       builder.spawn(TextBundle::from_section(
-        format!("Final score: {}", score.0),
+        format!("Final score: {} ({})", score.0, *difficulty),
         TextStyle {
           font_size: 32.,
           ..Default::default()
         },
       ));
+      builder.spawn(TextBundle::from_section(
+        if best > 0 && score.0 >= best {
+          "New high score!".to_string()
+        } else {
+          format!("High score: {}", best)
+        },
+        TextStyle {
+          font_size: 24.,
+          ..Default::default()
+        },
+      ));
       builder.spawn(TextBundle::from_section(
         "Press Space to try again",
         TextStyle {
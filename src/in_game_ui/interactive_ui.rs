@@ -5,11 +5,12 @@ use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
 use crate::asteroids::Asteroid;
 use crate::game_state::GameState;
 use crate::player::Player;
-use crate::shared::{StaticIndicator, GREEN, YELLOW};
+use crate::shared::{StaticIndicator, YELLOW};
 use crate::shared_events::{
   AsteroidDestroyedEvent, AsteroidSpawnedEvent, PowerUpCollectedEvent, StaticIndicatorSpawnEvent,
 };
 use crate::shared_resources::AsteroidCount;
+use crate::toroidal;
 
 const SPAWN_INDICATOR_THRESHOLD: i16 = 5;
 const INDICATOR_TRANSPARENCY: f32 = 0.25;
@@ -75,7 +76,7 @@ fn process_asteroid_count_change(
   let mut indicators_to_keep: Vec<Entity> = Vec::new();
   for (asteroid_transform, asteroid_entity) in asteroid_query.iter() {
     let asteroid_position = asteroid_transform.translation;
-    let direction = (asteroid_position - player_position).normalize();
+    let direction = toroidal::direction(player_position, asteroid_position);
     let indicator_position = player_position + direction * 50.0;
     let mesh_bundle = get_mesh_bundle(&mut meshes, &mut materials, indicator_position, YELLOW);
     if let Some((indicator_entity, _)) = indicator_query
@@ -130,7 +131,7 @@ fn update_dynamic_indicators_system(
     for (mut indicator_transform, indicator) in indicator_query.iter_mut() {
       if let Ok(asteroid_transform) = asteroid_query.get(indicator.target_entity) {
         let asteroid_position = asteroid_transform.translation;
-        let direction = (asteroid_position - player_position).normalize();
+        let direction = toroidal::direction(player_position, asteroid_position);
         indicator_transform.translation = player_position + direction * 50.0;
         indicator_transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction);
       }
@@ -153,9 +154,9 @@ fn spawn_static_indicator_event(
       return;
     };
 
-    let direction = (event.target_point - player_position).normalize();
+    let direction = toroidal::direction(player_position, event.target_point);
     let indicator_position = player_position + direction * 50.0;
-    let mesh_bundle = get_mesh_bundle(&mut meshes, &mut materials, indicator_position, GREEN);
+    let mesh_bundle = get_mesh_bundle(&mut meshes, &mut materials, indicator_position, event.colour);
     commands.spawn((
       mesh_bundle,
       StaticIndicator {
@@ -175,7 +176,7 @@ fn update_static_indicators_system(
   if let Ok(player_transform) = player_query.get_single() {
     let player_position = player_transform.translation;
     for (mut indicator_transform, indicator) in indicator_query.iter_mut() {
-      let direction = (indicator.target_point - player_position).normalize();
+      let direction = toroidal::direction(player_position, indicator.target_point);
       indicator_transform.translation = player_position + direction * 50.0;
       indicator_transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction);
     }
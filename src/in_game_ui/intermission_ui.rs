@@ -0,0 +1,172 @@
+use crate::game_state::GameState;
+use crate::in_game_ui::UiComponent;
+use crate::shared::{PowerUpType, YELLOW};
+use crate::shared_events::{WaveClearedEvent, WaveEvent};
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+
+pub struct IntermissionUiPlugin;
+
+impl Plugin for IntermissionUiPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(OnEnter(GameState::Starting), hide_intermission_ui_system)
+      .add_systems(OnEnter(GameState::Dead), hide_intermission_ui_system)
+      .add_systems(
+        OnEnter(GameState::ChoosingPowerUp),
+        set_intermission_ui_visibility(Visibility::Hidden),
+      )
+      .add_systems(
+        OnExit(GameState::ChoosingPowerUp),
+        set_intermission_ui_visibility(Visibility::Inherited),
+      )
+      .add_systems(
+        Update,
+        (wave_cleared_event, countdown_system, wave_started_event).run_if(in_state(GameState::Playing)),
+      );
+  }
+}
+
+#[derive(Component)] // Overlay shown between two waves
+struct IntermissionUi {
+  timer: Timer,
+}
+
+impl UiComponent for IntermissionUi {}
+
+#[derive(Component)]
+struct CountdownText;
+
+fn wave_cleared_event(
+  mut wave_cleared_events: EventReader<WaveClearedEvent>,
+  mut commands: Commands,
+  existing_ui: Query<Entity, With<IntermissionUi>>,
+) {
+  for event in wave_cleared_events.read() {
+    for entity in existing_ui.iter() {
+      commands.entity(entity).despawn_recursive();
+    }
+    commands
+      .spawn((
+        crate::in_game_ui::centered_overlay_base_ui(IntermissionUi {
+          timer: Timer::from_seconds(event.countdown, TimerMode::Once),
+        }),
+        Name::new("Intermission UI"),
+      ))
+      .with_children(|builder| {
+        builder.spawn(TextBundle::from_section(
+          format!("Wave {} cleared!", event.wave),
+          TextStyle {
+            font_size: 48.,
+            ..Default::default()
+          },
+        ));
+        builder.spawn(TextBundle::from_section(
+          format!(
+            "Time: {:.0}s   Accuracy: {:.0}%   Score: +{}",
+            event.clear_time,
+            event.accuracy * 100.,
+            event.score
+          ),
+          text_style(24., Color::WHITE),
+        ));
+        builder.spawn(TextBundle::from_section(
+          format!("Next up - wave {}", event.next_wave.wave),
+          text_style(32., YELLOW),
+        ));
+        for line in preview(&event.next_wave) {
+          builder.spawn(TextBundle::from_section(line, text_style(20., Color::WHITE)));
+        }
+        builder.spawn((
+          TextBundle::from_section(countdown_text(event.countdown), text_style(24., Color::WHITE)),
+          CountdownText,
+        ));
+      });
+  }
+}
+
+// Describes the composition of a wave, excluding reinforcements which are meant to be a surprise
+fn preview(event: &WaveEvent) -> Vec<String> {
+  let mut lines = vec![format!("{} asteroids", event.asteroid_count)];
+  let enemies = [
+    (event.small_ufo_count, "small UFO"),
+    (event.large_ufo_count, "large UFO"),
+    (event.swarm_count, "swarm"),
+    (event.mine_layer_count, "mine layer"),
+  ];
+  for (count, name) in enemies.iter().filter(|(count, _)| *count > 0) {
+    lines.push(format!("{} {}{}", count, name, if *count > 1 { "s" } else { "" }));
+  }
+  if event.morph_boss {
+    lines.push("Boss incoming!".to_string());
+  }
+  if event.shield_power_up {
+    lines.push("Shield power up".to_string());
+  }
+  if event.weapon_power_up {
+    lines.push("Weapon power up".to_string());
+  }
+  if let Some(secondary) = event.secondary_power_up {
+    lines.push(format!("{} power up", secondary));
+  }
+  if let Some(stat) = &event.stat_power_up {
+    lines.push(format!("{} power up", stat));
+  }
+  if let Some(effect) = event.timed_power_up {
+    lines.push(format!("{} power up", effect));
+  }
+  if event.choice_power_up {
+    lines.push(PowerUpType::Choice.to_string());
+  }
+  lines
+}
+
+fn countdown_text(remaining: f32) -> String {
+  format!("Starting in {:.0}... (press Enter to skip)", remaining.ceil())
+}
+
+fn text_style(font_size: f32, color: Color) -> TextStyle {
+  TextStyle {
+    font_size,
+    color,
+    ..Default::default()
+  }
+}
+
+fn countdown_system(
+  time: Res<Time>,
+  mut ui_query: Query<&mut IntermissionUi>,
+  mut text_query: Query<&mut Text, With<CountdownText>>,
+) {
+  for mut intermission_ui in ui_query.iter_mut() {
+    intermission_ui.timer.tick(time.delta());
+    for mut text in text_query.iter_mut() {
+      text.sections[0].value = countdown_text(intermission_ui.timer.remaining_secs());
+    }
+  }
+}
+
+fn wave_started_event(
+  mut wave_events: EventReader<WaveEvent>,
+  commands: Commands,
+  query: Query<Entity, With<IntermissionUi>>,
+) {
+  if wave_events.read().next().is_some() {
+    hide_intermission_ui_system(commands, query);
+  }
+}
+
+// The intermission overlay is hidden while the player chooses a power up so that the two don't overlap
+fn set_intermission_ui_visibility(visibility: Visibility) -> impl FnMut(Query<&mut Visibility, With<IntermissionUi>>) {
+  move |mut query| {
+    for mut ui_visibility in query.iter_mut() {
+      *ui_visibility = visibility;
+    }
+  }
+}
+
+fn hide_intermission_ui_system(mut commands: Commands, query: Query<Entity, With<IntermissionUi>>) {
+  for entity in query.iter() {
+    commands.entity(entity).despawn_recursive();
+  }
+}
@@ -1,12 +1,20 @@
 mod game_over_menu;
 mod interactive_ui;
+mod intermission_ui;
 mod pause_menu;
+mod power_up_choice_ui;
+mod start_menu;
 mod static_ui;
+mod upgrade_choice_ui;
 
 use crate::in_game_ui::game_over_menu::GameOverMenuPlugin;
 use crate::in_game_ui::interactive_ui::InteractiveUiPlugin;
+use crate::in_game_ui::intermission_ui::IntermissionUiPlugin;
 use crate::in_game_ui::pause_menu::PauseMenuPlugin;
+use crate::in_game_ui::power_up_choice_ui::PowerUpChoiceUiPlugin;
+use crate::in_game_ui::start_menu::StartMenuPlugin;
 use crate::in_game_ui::static_ui::StaticUiPlugin;
+use crate::in_game_ui::upgrade_choice_ui::UpgradeChoiceUiPlugin;
 use bevy::prelude::*;
 
 pub struct InGameUiPlugin;
@@ -15,8 +23,13 @@ impl Plugin for InGameUiPlugin {
   fn build(&self, app: &mut App) {
     app
       .add_plugins(InteractiveUiPlugin)
-      .add_plugins((GameOverMenuPlugin, PauseMenuPlugin))
-      .add_plugins(StaticUiPlugin);
+      .add_plugins((GameOverMenuPlugin, PauseMenuPlugin, PowerUpChoiceUiPlugin))
+      .add_plugins((
+        StartMenuPlugin,
+        StaticUiPlugin,
+        IntermissionUiPlugin,
+        UpgradeChoiceUiPlugin,
+      ));
   }
 }
 
@@ -0,0 +1,136 @@
+use crate::game_state::GameState;
+use crate::in_game_ui::UiComponent;
+use crate::shared::{PowerUpType, YELLOW};
+use crate::shared_events::{PowerUpChoiceEvent, PowerUpCollectedEvent};
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+const CHOICE_KEYS: [KeyCode; 3] = [KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3];
+
+pub struct PowerUpChoiceUiPlugin;
+
+impl Plugin for PowerUpChoiceUiPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<PendingPowerUpChoices>()
+      .add_systems(OnEnter(GameState::Starting), clear_pending_choices_system)
+      .add_systems(OnEnter(GameState::ChoosingPowerUp), show_power_up_choice_ui_system)
+      .add_systems(OnExit(GameState::ChoosingPowerUp), hide_power_up_choice_ui_system)
+      .add_systems(Update, power_up_choice_event.run_if(in_state(GameState::Playing)))
+      .add_systems(
+        Update,
+        choose_power_up_system.run_if(in_state(GameState::ChoosingPowerUp)),
+      );
+  }
+}
+
+// Choices are offered one after the other if several arrive at the same time
+#[derive(Resource, Default)]
+struct PendingPowerUpChoices(VecDeque<Vec<PowerUpType>>);
+
+#[derive(Component)] // Overlay shown while the game is paused for the player to choose a power up
+struct PowerUpChoiceUi;
+
+impl UiComponent for PowerUpChoiceUi {}
+
+fn power_up_choice_event(
+  mut power_up_choice_events: EventReader<PowerUpChoiceEvent>,
+  mut pending_choices: ResMut<PendingPowerUpChoices>,
+  mut next_game_state: ResMut<NextState<GameState>>,
+) {
+  for event in power_up_choice_events.read() {
+    if !event.options.is_empty() {
+      pending_choices.0.push_back(event.options.clone());
+    }
+  }
+  if !pending_choices.0.is_empty() {
+    next_game_state.set(GameState::ChoosingPowerUp);
+  }
+}
+
+fn show_power_up_choice_ui_system(
+  mut commands: Commands,
+  pending_choices: Res<PendingPowerUpChoices>,
+  mut time: ResMut<Time<Virtual>>,
+) {
+  let Some(options) = pending_choices.0.front() else {
+    return;
+  };
+  time.pause();
+  commands
+    .spawn((
+      crate::in_game_ui::centered_overlay_base_ui(PowerUpChoiceUi),
+      Name::new("Power Up Choice UI"),
+    ))
+    .with_children(|builder| {
+      builder.spawn(TextBundle::from_section(
+        "Choose a power up",
+        TextStyle {
+          font_size: 48.,
+          ..default()
+        },
+      ));
+      builder
+        .spawn(NodeBundle {
+          style: Style {
+            column_gap: Val::Px(30.),
+            ..default()
+          },
+          ..default()
+        })
+        .with_children(|builder| {
+          for (i, option) in options.iter().enumerate() {
+            builder.spawn(TextBundle::from_section(
+              format!("[{}] {}", i + 1, option),
+              TextStyle {
+                font_size: 24.,
+                color: YELLOW,
+                ..default()
+              },
+            ));
+          }
+        });
+    });
+}
+
+// Applies the chosen power up as if the player had collected it
+fn choose_power_up_system(
+  keyboard_input: Res<ButtonInput<KeyCode>>,
+  mut pending_choices: ResMut<PendingPowerUpChoices>,
+  mut next_game_state: ResMut<NextState<GameState>>,
+  mut power_up_collected_event: EventWriter<PowerUpCollectedEvent>,
+) {
+  let Some(options) = pending_choices.0.front() else {
+    next_game_state.set(GameState::Playing);
+    return;
+  };
+  let Some(index) = CHOICE_KEYS.iter().position(|key| keyboard_input.just_pressed(*key)) else {
+    return;
+  };
+  let Some(power_up_type) = options.get(index).cloned() else {
+    return;
+  };
+  info!("Power up chosen: {:?}", power_up_type);
+  power_up_collected_event.send(PowerUpCollectedEvent {
+    entity: Entity::PLACEHOLDER,
+    power_up_type,
+  });
+  pending_choices.0.pop_front();
+  next_game_state.set(GameState::Playing);
+}
+
+fn hide_power_up_choice_ui_system(
+  mut commands: Commands,
+  query: Query<Entity, With<PowerUpChoiceUi>>,
+  mut time: ResMut<Time<Virtual>>,
+) {
+  for entity in query.iter() {
+    commands.entity(entity).despawn_recursive();
+  }
+  time.unpause();
+}
+
+fn clear_pending_choices_system(mut pending_choices: ResMut<PendingPowerUpChoices>) {
+  pending_choices.0.clear();
+}
@@ -0,0 +1,115 @@
+use crate::difficulty::{Difficulty, HighScores};
+use crate::game_state::GameState;
+use crate::in_game_ui::UiComponent;
+use crate::shared::{PURPLE, YELLOW};
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+
+const HIGH_SCORES_SHOWN: usize = 5;
+
+pub struct StartMenuPlugin;
+
+impl Plugin for StartMenuPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(OnEnter(GameState::Starting), show_start_menu_system)
+      .add_systems(OnExit(GameState::Starting), hide_start_menu_system)
+      .add_systems(
+        Update,
+        highlight_difficulty_system.run_if(in_state(GameState::Starting)),
+      );
+  }
+}
+
+#[derive(Component)] // Static overlay shown before the game starts
+struct StartMenuUi;
+
+impl UiComponent for StartMenuUi {}
+
+#[derive(Component)]
+struct DifficultyOption(Difficulty);
+
+fn show_start_menu_system(mut commands: Commands, difficulty: Res<Difficulty>, high_scores: Res<HighScores>) {
+  commands
+    .spawn((
+      crate::in_game_ui::centered_overlay_base_ui(StartMenuUi),
+      Name::new("Start Menu"),
+    ))
+    .with_children(|builder| {
+      builder.spawn(TextBundle::from_section(
+        "Select difficulty",
+        TextStyle {
+          font_size: 32.,
+          ..Default::default()
+        },
+      ));
+      builder
+        .spawn(NodeBundle {
+          style: Style {
+            column_gap: Val::Px(24.),
+            ..Default::default()
+          },
+          ..Default::default()
+        })
+        .with_children(|builder| {
+          for (i, option) in Difficulty::ALL.iter().enumerate() {
+            builder.spawn((
+              TextBundle::from_section(
+                format!("[{}] {}", i + 1, option),
+                TextStyle {
+                  font_size: 24.,
+                  color: difficulty_color(*option, *difficulty),
+                  ..Default::default()
+                },
+              ),
+              DifficultyOption(*option),
+            ));
+          }
+        });
+      builder.spawn(TextBundle::from_section(
+        "Press Space to start",
+        TextStyle {
+          font_size: 24.,
+          ..Default::default()
+        },
+      ));
+      for (i, high_score) in high_scores.0.iter().take(HIGH_SCORES_SHOWN).enumerate() {
+        builder.spawn(TextBundle::from_section(
+          format!(
+            "{}. {} - wave {} on {}",
+            i + 1,
+            high_score.score,
+            high_score.wave,
+            high_score.difficulty
+          ),
+          TextStyle {
+            font_size: 20.,
+            ..Default::default()
+          },
+        ));
+      }
+    });
+}
+
+fn highlight_difficulty_system(difficulty: Res<Difficulty>, mut query: Query<(&DifficultyOption, &mut Text)>) {
+  if !difficulty.is_changed() {
+    return;
+  }
+  for (option, mut text) in query.iter_mut() {
+    text.sections[0].style.color = difficulty_color(option.0, *difficulty);
+  }
+}
+
+fn difficulty_color(option: Difficulty, selected: Difficulty) -> Color {
+  if option == selected {
+    YELLOW
+  } else {
+    PURPLE
+  }
+}
+
+fn hide_start_menu_system(mut commands: Commands, query: Query<Entity, With<StartMenuUi>>) {
+  for entity in query.iter() {
+    commands.entity(entity).despawn_recursive();
+  }
+}
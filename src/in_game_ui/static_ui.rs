@@ -1,7 +1,12 @@
+use crate::difficulty::Difficulty;
 use crate::game_state::GameState;
 use crate::in_game_ui::UiComponent;
+use crate::player::Player;
+use crate::player_laser::Laser;
+use crate::shared::{WeaponSystem, ORANGE, PURPLE, RED, YELLOW};
 use crate::shared_events::{AsteroidDestroyedEvent, AsteroidSpawnedEvent, ScoreEvent, WaveEvent};
 use crate::shared_resources::{AsteroidCount, Score};
+use crate::timed_power_ups::TimedEffects;
 use bevy::app::{App, Plugin, Update};
 use bevy::prelude::*;
 
@@ -19,7 +24,17 @@ impl Plugin for StaticUiPlugin {
         (hide_static_ui_system, hide_message_ui_system),
       )
       .add_systems(OnEnter(GameState::Paused), hide_message_ui_system)
-      .add_systems(Update, (current_wave_event, change_message_visibility_system))
+      .add_systems(
+        Update,
+        (
+          current_wave_event,
+          change_message_visibility_system,
+          secondary_weapon_system,
+          laser_energy_system,
+          weapon_heat_system,
+          timed_effects_system,
+        ),
+      )
       .add_systems(
         Update,
         (
@@ -42,6 +57,18 @@ struct AsteroidCountComponent;
 #[derive(Component)]
 struct WaveComponent;
 
+#[derive(Component)]
+struct SecondaryWeaponComponent;
+
+#[derive(Component)] // Fill of the laser energy bar
+struct LaserEnergyComponent;
+
+#[derive(Component)] // Fill of the weapon heat gauge
+struct WeaponHeatComponent;
+
+#[derive(Component)] // Remaining time of all active timed effects
+struct TimedEffectsComponent;
+
 #[derive(Component)] // UI at the top of the screen
 struct StaticUi;
 
@@ -109,6 +136,65 @@ fn show_static_ui_system(mut commands: Commands) {
         },
         WaveComponent,
       ));
+      commands.spawn((
+        TextBundle {
+          text: Text::from_section(
+            format!("{} -", SECONDARY_LABEL),
+            TextStyle {
+              font_size: 32.,
+              ..default()
+            },
+          ),
+          ..default()
+        },
+        SecondaryWeaponComponent,
+      ));
+      spawn_meter(commands, PURPLE, LaserEnergyComponent);
+      spawn_meter(commands, ORANGE, WeaponHeatComponent);
+      commands.spawn((
+        TextBundle {
+          text: Text::from_section(
+            "",
+            TextStyle {
+              font_size: 32.,
+              color: YELLOW,
+              ..default()
+            },
+          ),
+          ..default()
+        },
+        TimedEffectsComponent,
+      ));
+    });
+}
+
+// A horizontal bar whose fill (marked with the given component) can be resized to show the current value
+fn spawn_meter<T: Component>(commands: &mut ChildBuilder, color: Color, fill_component: T) {
+  commands
+    .spawn(NodeBundle {
+      style: Style {
+        width: Val::Px(METER_WIDTH),
+        height: Val::Px(12.),
+        margin: UiRect::top(Val::Px(10.)),
+        border: UiRect::all(Val::Px(2.)),
+        ..default()
+      },
+      border_color: BorderColor(color),
+      ..default()
+    })
+    .with_children(|commands| {
+      commands.spawn((
+        NodeBundle {
+          style: Style {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+          },
+          background_color: BackgroundColor(color),
+          ..default()
+        },
+        fill_component,
+      ));
     });
 }
 
@@ -121,15 +207,21 @@ fn hide_static_ui_system(mut commands: Commands, query: Query<Entity, With<Stati
 const SCORE_LABEL: &'static str = "Score:";
 const ASTEROIDS_LABEL: &'static str = "Asteroids:";
 const WAVE_LABEL: &'static str = "Wave:";
+const SECONDARY_LABEL: &'static str = "Secondary:";
+const METER_WIDTH: f32 = 120.;
 
 fn process_score_event(
   mut ui_event: EventReader<ScoreEvent>,
   mut score: ResMut<Score>,
   mut score_text: Query<&mut Text, (With<ScoreComponent>, Without<AsteroidCountComponent>)>,
+  difficulty: Res<Difficulty>,
+  timed_effects: Res<TimedEffects>,
 ) {
   for event in ui_event.read() {
     for mut text in score_text.iter_mut() {
-      score.0 += event.score;
+      let multiplier = difficulty.score_multiplier() * timed_effects.score_multiplier();
+      let points = (event.score as f32 * multiplier).round() as u16;
+      score.0 = score.0.saturating_add(points);
       text.sections[0].value = format!("{} {}", SCORE_LABEL, score.0);
     }
   }
@@ -174,6 +266,70 @@ fn process_asteroid_spawn_event(
   }
 }
 
+fn secondary_weapon_system(
+  weapon_query: Query<&WeaponSystem, (With<Player>, Changed<WeaponSystem>)>,
+  mut secondary_texts: Query<&mut Text, With<SecondaryWeaponComponent>>,
+) {
+  for weapon_system in weapon_query.iter() {
+    let value = match weapon_system.secondary {
+      Some(secondary) => format!("{} {} x{}", SECONDARY_LABEL, secondary.weapon_type, secondary.ammo),
+      None => format!("{} -", SECONDARY_LABEL),
+    };
+    for mut text in secondary_texts.iter_mut() {
+      if text.sections[0].value != value {
+        text.sections[0].value = value.clone();
+      }
+    }
+  }
+}
+
+fn laser_energy_system(
+  laser_query: Query<&Laser, (With<Player>, Changed<Laser>)>,
+  mut energy_bars: Query<&mut Style, With<LaserEnergyComponent>>,
+) {
+  for laser in laser_query.iter() {
+    for mut style in energy_bars.iter_mut() {
+      style.width = Val::Percent(100. * laser.energy / laser.max_energy);
+    }
+  }
+}
+
+fn weapon_heat_system(
+  weapon_query: Query<&WeaponSystem, (With<Player>, Changed<WeaponSystem>)>,
+  mut heat_gauges: Query<(&mut Style, &mut BackgroundColor), With<WeaponHeatComponent>>,
+) {
+  for weapon_system in weapon_query.iter() {
+    for (mut style, mut color) in heat_gauges.iter_mut() {
+      style.width = Val::Percent(100. * weapon_system.heat.value / weapon_system.heat.max);
+      *color = BackgroundColor(if weapon_system.heat.is_overheated() {
+        RED
+      } else {
+        ORANGE
+      });
+    }
+  }
+}
+
+fn timed_effects_system(
+  timed_effects: Res<TimedEffects>,
+  mut timed_effect_texts: Query<&mut Text, With<TimedEffectsComponent>>,
+) {
+  if !timed_effects.is_changed() {
+    return;
+  }
+  let value = timed_effects
+    .active
+    .iter()
+    .map(|(effect, remaining)| format!("{} {:.0}s", effect, remaining.ceil()))
+    .collect::<Vec<_>>()
+    .join("  ");
+  for mut text in timed_effect_texts.iter_mut() {
+    if text.sections[0].value != value {
+      text.sections[0].value = value.clone();
+    }
+  }
+}
+
 fn reset_static_ui_system(
   mut score_texts: Query<&mut Text, (With<ScoreComponent>, Without<AsteroidCountComponent>)>,
   mut score: ResMut<Score>,
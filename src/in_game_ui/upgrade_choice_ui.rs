@@ -0,0 +1,107 @@
+use crate::game_state::GameState;
+use crate::in_game_ui::UiComponent;
+use crate::player::Player;
+use crate::player_weapon::BRANCH_KEYS;
+use crate::shared::{UpgradeTree, WeaponSystem, PURPLE, YELLOW};
+use crate::weapon_progression::{PlayerWeaponProgression, WeaponProgression};
+use bevy::app::{App, Plugin, Update};
+use bevy::prelude::*;
+
+pub struct UpgradeChoiceUiPlugin;
+
+impl Plugin for UpgradeChoiceUiPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(OnEnter(GameState::Dead), hide_upgrade_choice_ui_system)
+      .add_systems(Update, upgrade_choice_ui_system.run_if(in_state(GameState::Playing)));
+  }
+}
+
+#[derive(Component)] // Shown at the bottom of the screen while there are weapon upgrades to choose
+struct UpgradeChoiceUi {
+  upgrades: UpgradeTree,
+}
+
+impl UiComponent for UpgradeChoiceUi {}
+
+// Rebuilds the overlay whenever the upgrade tree changes and removes it once there's nothing left to choose
+fn upgrade_choice_ui_system(
+  mut commands: Commands,
+  player_query: Query<&WeaponSystem, With<Player>>,
+  ui_query: Query<(Entity, &UpgradeChoiceUi)>,
+  progressions: Res<Assets<WeaponProgression>>,
+  progression: Res<PlayerWeaponProgression>,
+) {
+  let upgrades = player_query
+    .get_single()
+    .map_or(UpgradeTree::default(), |weapons| weapons.upgrades);
+  if ui_query.iter().any(|(_, ui)| ui.upgrades == upgrades) {
+    return;
+  }
+  for (entity, _) in ui_query.iter() {
+    commands.entity(entity).despawn_recursive();
+  }
+  let Some(progression) = progressions.get(&progression.0) else {
+    return;
+  };
+  if upgrades.pending == 0 {
+    return;
+  }
+
+  commands
+    .spawn((
+      NodeBundle {
+        style: Style {
+          position_type: PositionType::Absolute,
+          bottom: Val::Px(15.),
+          width: Val::Percent(100.),
+          flex_direction: FlexDirection::Column,
+          align_items: AlignItems::Center,
+          row_gap: Val::Px(6.),
+          ..default()
+        },
+        ..default()
+      },
+      UpgradeChoiceUi { upgrades },
+      Name::new("Upgrade Choice UI"),
+    ))
+    .with_children(|builder| {
+      builder.spawn(TextBundle::from_section(
+        format!("Choose a weapon upgrade ({} left)", upgrades.pending),
+        TextStyle {
+          font_size: 24.,
+          color: YELLOW,
+          ..default()
+        },
+      ));
+      builder
+        .spawn(NodeBundle {
+          style: Style {
+            column_gap: Val::Px(30.),
+            ..default()
+          },
+          ..default()
+        })
+        .with_children(|builder| {
+          for (i, (_, branch)) in BRANCH_KEYS.iter().enumerate() {
+            let max_level = WeaponSystem::max_branch_level(*branch, progression);
+            let level = upgrades.level(*branch);
+            let color = if level < max_level { Color::WHITE } else { PURPLE };
+            builder.spawn(TextBundle::from_section(
+              format!("[{}] {} {}/{}", i + 1, branch, level, max_level),
+              TextStyle {
+                font_size: 20.,
+                color,
+                ..default()
+              },
+            ));
+          }
+        });
+    });
+}
+
+fn hide_upgrade_choice_ui_system(mut commands: Commands, query: Query<Entity, With<UpgradeChoiceUi>>) {
+  for entity in query.iter() {
+    commands.entity(entity).despawn_recursive();
+  }
+}
@@ -17,12 +17,15 @@ use crate::asteroids::AsteroidPlugin;
 use crate::background_stars::BackgroundStarsPlugin;
 use crate::camera::PixelPerfectCameraPlugin;
 use crate::collision::CollisionPlugin;
+use crate::difficulty::DifficultyPlugin;
+use crate::difficulty_director::DifficultyDirectorPlugin;
 use crate::enemies::EnemyPlugin;
 use crate::explosion::ExplosionPlugin;
 use crate::game_state::{GameState, GameStatePlugin};
 use crate::game_world::GameWorldPlugin;
 use crate::in_game_ui::InGameUiPlugin;
 use crate::player::PlayerPlugin;
+use crate::player_laser::PlayerLaserPlugin;
 use crate::player_shield::PlayerShieldPlugin;
 use crate::player_weapon::PlayerWeaponPlugin;
 use crate::power_ups::PowerUpPlugin;
@@ -30,18 +33,24 @@ use crate::projectile::ProjectilePlugin;
 use crate::shared::VERY_DARK_2;
 use crate::shared_events::SharedEventsPlugin;
 use crate::shared_resources::SharedResourcesPlugin;
+use crate::timed_power_ups::TimedPowerUpPlugin;
+use crate::wave_generator::WaveGeneratorPlugin;
 use crate::waves::WavesPlugin;
+use crate::weapon_progression::WeaponProgressionPlugin;
 
 mod asteroids;
 mod background_stars;
 mod camera;
 mod collision;
+mod difficulty;
+mod difficulty_director;
 mod enemies;
 mod explosion;
 mod game_state;
 mod game_world;
 mod in_game_ui;
 mod player;
+mod player_laser;
 mod player_shield;
 mod player_weapon;
 mod power_ups;
@@ -49,13 +58,18 @@ mod projectile;
 mod shared;
 mod shared_events;
 mod shared_resources;
+#[cfg(test)]
+mod test_utils;
+mod timed_power_ups;
+mod toroidal;
+mod wave_generator;
 mod waves;
+mod weapon_progression;
 
 const WINDOW_WIDTH: f32 = 1280.;
 const WINDOW_HEIGHT: f32 = 720.;
 
 // TODO: Change player sprite when upgrading weapon system
-// TODO: Consider adding other power ups, esp. ship upgrades (more weapons, maneuverability, speed), etc.
 // TODO: Consider adding multiplayer
 // TODO: Add another/stronger/smarter enemies
 // TODO: Set exclusion layers for collisions (enemy, asteroids vs power ups, and player projectiles)
@@ -103,10 +117,19 @@ fn main() {
       AsteroidPlugin,
       GameStatePlugin,
       WavesPlugin,
+      WaveGeneratorPlugin,
+      DifficultyPlugin,
+      DifficultyDirectorPlugin,
       EnemyPlugin,
       PowerUpPlugin,
     ))
-    .add_plugins((CollisionPlugin, ExplosionPlugin))
+    .add_plugins((
+      WeaponProgressionPlugin,
+      PlayerLaserPlugin,
+      CollisionPlugin,
+      ExplosionPlugin,
+      TimedPowerUpPlugin,
+    ))
     .add_plugins(InGameUiPlugin)
     .insert_state(GameState::Starting)
     .insert_resource(Msaa::Off)
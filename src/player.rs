@@ -1,19 +1,42 @@
 use crate::game_state::GameState;
-use crate::game_world::WORLD_SIZE;
 use crate::shared::{
-  get_player_collision_groups, player_projectile_collision_groups, Category, EntityType, ImpactInfo, ProjectileInfo,
-  Substance, WeaponSystem, PURPLE,
+  get_player_collision_groups, player_projectile_collision_groups, Category, EntityType, HomingInfo, ImpactInfo,
+  PowerUpType, ProjectileInfo, SecondaryWeaponType, Substance, WeaponSystem, WrapAroundEntity, GREEN, ORANGE, PURPLE,
+  RED,
 };
 use crate::shared_events::{NextWaveEvent, PowerUpCollectedEvent, ProjectileSpawnEvent, ResetLoadoutEvent};
+use crate::timed_power_ups::TimedEffects;
 use bevy::audio::Volume;
 use bevy::prelude::*;
 use bevy_enoki::prelude::{OneShot, ParticleSpawnerBundle, DEFAULT_MATERIAL};
 use bevy_rapier2d::dynamics::RigidBody;
 use bevy_rapier2d::prelude::*;
 
-pub const SHOOTING_COOLDOWN: f32 = 0.1;
 const MOVEMENT_SPEED: f32 = 125.;
-const DAMAGE: u16 = 3;
+const MAX_MOVEMENT_SPEED: f32 = 200.;
+const MOVEMENT_SPEED_PER_POWER_UP: f32 = 15.;
+const ROTATION_SPEED: f32 = 5.;
+const MAX_ROTATION_SPEED: f32 = 8.;
+const ROTATION_SPEED_PER_POWER_UP: f32 = 0.6;
+const HEAT_PER_MUZZLE: f32 = 3.5; // At 10 shots per second, even a single muzzle outpaces the dissipation
+const HEAT_DISSIPATION_PER_SECOND: f32 = 25.;
+const OVERHEAT_LOCK_TIME: f32 = 1.5;
+const CHARGE_THRESHOLD: f32 = 0.5; // Seconds the fire button needs to be held before a shot starts charging
+const MAX_CHARGE_TIME: f32 = 2.;
+const CHARGE_SHOT_MIN_DAMAGE_FACTOR: f32 = 3.;
+const CHARGE_SHOT_MAX_DAMAGE_FACTOR: f32 = 10.;
+const CHARGE_SHOT_MAX_SIZE_FACTOR: f32 = 2.;
+const CHARGE_SHOT_MIN_PIERCE: u8 = 2;
+const CHARGE_SHOT_MAX_PIERCE: u8 = 8;
+const CHARGE_SHOT_HEAT: f32 = 30.;
+const CHARGE_SHOT_OFFSET: f32 = 20.;
+const SECONDARY_WEAPON_KEY: KeyCode = KeyCode::ShiftLeft;
+const MISSILE_DAMAGE: u16 = 12;
+const MINE_DAMAGE: u16 = 20;
+const MINE_LIFE_TIME: f32 = 10.;
+const SHOTGUN_DAMAGE: u16 = 4;
+const SHOTGUN_PELLETS: u16 = 7;
+const SHOTGUN_SPREAD: f32 = 0.7; // Radians
 
 pub struct PlayerPlugin;
 
@@ -21,13 +44,15 @@ impl Plugin for PlayerPlugin {
   fn build(&self, app: &mut App) {
     app
       .add_systems(OnEnter(GameState::Starting), spawn_player_system)
+      .add_systems(OnEnter(GameState::Dead), despawn_charge_particles_system)
       .add_systems(
         Update,
         (
           player_movement_system,
           player_shooting_system,
           other_controls_system,
-          player_wraparound_system,
+          ship_stat_power_up_event,
+          reset_ship_stats_event,
         ),
       );
   }
@@ -42,20 +67,20 @@ pub struct Player {
 #[derive(Component)]
 struct ExhaustParticles;
 
+#[derive(Component)]
+struct ChargeParticles;
+
 fn spawn_player_system(mut commands: Commands, asset_server: Res<AssetServer>) {
-  let player_handle = asset_server.load("sprites/player_1.png");
   let audio_handle = asset_server.load("audio/spaceship_loop_default.ogg");
 
+  // The sprite and weapon system are set up from the first level of the player's weapon progression once spawned
   commands.spawn((
-    SpriteBundle {
-      texture: player_handle,
-      ..default()
-    },
+    SpriteBundle::default(),
     Player {
       movement_speed: MOVEMENT_SPEED,
-      rotation_speed: 5.,
+      rotation_speed: ROTATION_SPEED,
     },
-    WeaponSystem::new(SHOOTING_COOLDOWN, 20.),
+    WeaponSystem::default(),
     Name::new("Player"),
     RigidBody::Dynamic,
     Collider::ball(9.),
@@ -73,6 +98,7 @@ fn spawn_player_system(mut commands: Commands, asset_server: Res<AssetServer>) {
     AdditionalMassProperties::Mass(2.),
     get_player_collision_groups(),
     Ccd::enabled(),
+    WrapAroundEntity,
     AudioBundle {
       source: audio_handle,
       settings: PlaybackSettings {
@@ -141,19 +167,32 @@ fn player_movement_system(
 }
 
 fn player_shooting_system(
+  mut commands: Commands,
   time: Res<Time>,
-  mut query: Query<(&Transform, &mut WeaponSystem), With<Player>>,
+  asset_server: Res<AssetServer>,
+  mut query: Query<(Entity, &Transform, &mut WeaponSystem), With<Player>>,
+  charge_particles_query: Query<Entity, With<ChargeParticles>>,
   keyboard_input: Res<ButtonInput<KeyCode>>,
+  timed_effects: Res<TimedEffects>,
   mut projective_spawn_event: EventWriter<ProjectileSpawnEvent>,
 ) {
-  for (player_transform, mut weapon_system) in query.iter_mut() {
+  for (player_entity, player_transform, mut weapon_system) in query.iter_mut() {
+    // The fire button fires normally, but holding it for longer than the threshold stops firing and charges a shot
+    let is_fire_pressed = keyboard_input.pressed(KeyCode::Space) && !weapon_system.heat.is_overheated();
+    let was_charging = weapon_system.charge_time >= CHARGE_THRESHOLD;
+    if is_fire_pressed {
+      weapon_system.charge_time = (weapon_system.charge_time + time.delta_seconds()).min(MAX_CHARGE_TIME);
+    }
+    let is_charging = weapon_system.charge_time >= CHARGE_THRESHOLD;
+
     // Spawn a projectile if the player is shooting
-    if keyboard_input.pressed(KeyCode::Space) && weapon_system.shooting_cooldown <= 0. {
+    if is_fire_pressed && !is_charging && weapon_system.shooting_cooldown <= 0. {
+      let primary_projectile = weapon_system.primary_projectile;
       let info = ProjectileInfo {
-        damage: DAMAGE,
-        speed: 750.,
-        max_life_time: 0.4,
-        cooldown: 0.1,
+        damage: primary_projectile.damage,
+        speed: primary_projectile.speed,
+        max_life_time: primary_projectile.max_life_time,
+        cooldown: primary_projectile.cooldown,
         collider: Collider::cuboid(0.5, 2.5),
         collision_groups: player_projectile_collision_groups(),
         sprite: Sprite {
@@ -162,8 +201,11 @@ fn player_shooting_system(
           ..default()
         },
         by: EntityType::Player,
+        homing: None,
+        pierce: primary_projectile.pierce,
+        ricochet: primary_projectile.ricochet,
       };
-      weapon_system.shooting_cooldown = info.cooldown;
+      weapon_system.shooting_cooldown = info.cooldown * timed_effects.cooldown_factor();
       for weapon in &weapon_system.primary {
         let spawn_position = player_transform.translation + (player_transform.rotation * weapon.origin_offset);
         let direction = player_transform.rotation * weapon.direction;
@@ -176,12 +218,241 @@ fn player_shooting_system(
           spawn_position,
         });
       }
+
+      // Every muzzle heats up the weapon, so that higher weapon levels can't be fired continuously
+      let muzzle_count = weapon_system.primary.len() as f32;
+      add_heat(
+        &mut weapon_system,
+        HEAT_PER_MUZZLE * muzzle_count,
+        &mut commands,
+        &asset_server,
+      );
+    }
+
+    // Release the charged shot once the fire button is let go, unless the weapon has overheated in the meantime
+    if !is_fire_pressed {
+      if is_charging && !weapon_system.heat.is_overheated() {
+        fire_charge_shot(&weapon_system, player_transform, &mut projective_spawn_event);
+        commands.spawn(AudioBundle {
+          source: asset_server.load("audio/shoot_laser_big.ogg"),
+          settings: PlaybackSettings {
+            mode: bevy::audio::PlaybackMode::Remove,
+            volume: Volume::new(0.8),
+            ..Default::default()
+          },
+        });
+        add_heat(&mut weapon_system, CHARGE_SHOT_HEAT, &mut commands, &asset_server);
+      }
+      weapon_system.charge_time = 0.;
+    }
+
+    // Show the charging effect on the ship while a shot is being charged
+    let is_charging = weapon_system.charge_time >= CHARGE_THRESHOLD;
+    if is_charging && !was_charging {
+      commands.entity(player_entity).with_children(|builder| {
+        builder.spawn((
+          ParticleSpawnerBundle {
+            effect: asset_server.load("particles/charge.ron"),
+            material: DEFAULT_MATERIAL,
+            transform: Transform {
+              translation: Vec3::new(0., CHARGE_SHOT_OFFSET, 50.),
+              scale: Vec3::splat(0.3),
+              ..default()
+            },
+            ..default()
+          },
+          ChargeParticles,
+        ));
+      });
+    } else if !is_charging && was_charging {
+      for entity in charge_particles_query.iter() {
+        commands.entity(entity).despawn();
+      }
     }
 
-    // Update the shooting cooldown
+    // Fire the secondary weapon, if the player has one with ammo left
+    if let Some(secondary) = weapon_system.secondary.as_mut() {
+      if keyboard_input.pressed(SECONDARY_WEAPON_KEY) && secondary.cooldown <= 0. && secondary.ammo > 0 {
+        secondary.ammo -= 1;
+        secondary.cooldown = secondary.weapon_type.cooldown();
+        fire_secondary_weapon(secondary.weapon_type, player_transform, &mut projective_spawn_event);
+      }
+      if secondary.cooldown > 0. {
+        secondary.cooldown -= time.delta_seconds();
+      }
+    }
+
+    // Update the shooting cooldowns and let the weapon cool down
     if weapon_system.shooting_cooldown > 0. {
       weapon_system.shooting_cooldown -= time.delta_seconds();
     }
+    let heat = &mut weapon_system.heat;
+    heat.value = (heat.value - HEAT_DISSIPATION_PER_SECOND * time.delta_seconds()).max(0.);
+    heat.lock_time = (heat.lock_time - time.delta_seconds()).max(0.);
+  }
+}
+
+fn despawn_charge_particles_system(mut commands: Commands, query: Query<Entity, With<ChargeParticles>>) {
+  for entity in query.iter() {
+    commands.entity(entity).despawn();
+  }
+}
+
+// Raises the heat of the primary weapon and locks it if it overheats
+fn add_heat(weapon_system: &mut WeaponSystem, amount: f32, commands: &mut Commands, asset_server: &Res<AssetServer>) {
+  let heat = &mut weapon_system.heat;
+  heat.value = (heat.value + amount).min(heat.max);
+  if heat.value >= heat.max {
+    heat.lock_time = OVERHEAT_LOCK_TIME;
+    commands.spawn(AudioBundle {
+      source: asset_server.load("audio/weapon_overheat.ogg"),
+      settings: PlaybackSettings {
+        mode: bevy::audio::PlaybackMode::Remove,
+        volume: Volume::new(0.8),
+        speed: 0.8,
+        ..Default::default()
+      },
+    });
+    debug!("Player weapon overheated");
+  }
+}
+
+// A single large projectile which passes through several targets, with its damage, size and the number of targets it
+// pierces scaling with the time the shot was charged for
+fn fire_charge_shot(
+  weapon_system: &WeaponSystem,
+  player_transform: &Transform,
+  projective_spawn_event: &mut EventWriter<ProjectileSpawnEvent>,
+) {
+  let charge = ((weapon_system.charge_time - CHARGE_THRESHOLD) / (MAX_CHARGE_TIME - CHARGE_THRESHOLD)).clamp(0., 1.);
+  let primary_projectile = weapon_system.primary_projectile;
+  let damage_factor =
+    CHARGE_SHOT_MIN_DAMAGE_FACTOR + (CHARGE_SHOT_MAX_DAMAGE_FACTOR - CHARGE_SHOT_MIN_DAMAGE_FACTOR) * charge;
+  let size = Vec2::new(2., 6.) * (1. + CHARGE_SHOT_MAX_SIZE_FACTOR * charge);
+  let forward = player_transform.rotation * Vec3::Y;
+  let mut info = secondary_projectile_info(
+    (primary_projectile.damage as f32 * damage_factor).round() as u16,
+    primary_projectile.speed * 0.8,
+    primary_projectile.max_life_time * 2.,
+    size,
+    PURPLE,
+  );
+  info.pierce =
+    CHARGE_SHOT_MIN_PIERCE + ((CHARGE_SHOT_MAX_PIERCE - CHARGE_SHOT_MIN_PIERCE) as f32 * charge).round() as u8;
+  projective_spawn_event.send(ProjectileSpawnEvent {
+    projectile_info: info,
+    origin_rotation: player_transform.rotation,
+    origin_forward: forward,
+    spawn_position: player_transform.translation + forward * CHARGE_SHOT_OFFSET,
+  });
+  debug!(
+    "Fired charge shot (charge: {:.2}, damage factor: {:.1})",
+    charge, damage_factor
+  );
+}
+
+fn fire_secondary_weapon(
+  weapon_type: SecondaryWeaponType,
+  player_transform: &Transform,
+  projective_spawn_event: &mut EventWriter<ProjectileSpawnEvent>,
+) {
+  let forward = player_transform.rotation * Vec3::Y;
+  match weapon_type {
+    // A homing missile which seeks out the nearest enemy in front of the player
+    SecondaryWeaponType::Missiles => {
+      let mut info = secondary_projectile_info(MISSILE_DAMAGE, 250., 3., Vec2::new(2., 6.), ORANGE);
+      info.homing = Some(HomingInfo {
+        turn_rate: 4.,
+        acquisition_cone: 0.8,
+        acquisition_range: 400.,
+        fuel: 2.5,
+      });
+      projective_spawn_event.send(ProjectileSpawnEvent {
+        projectile_info: info,
+        origin_rotation: player_transform.rotation,
+        origin_forward: forward,
+        spawn_position: player_transform.translation + forward * 15.,
+      });
+    }
+    // A stationary mine which is left behind the player and damages whatever runs into it
+    SecondaryWeaponType::MineDropper => {
+      projective_spawn_event.send(ProjectileSpawnEvent {
+        projectile_info: secondary_projectile_info(MINE_DAMAGE, 0., MINE_LIFE_TIME, Vec2::splat(6.), RED),
+        origin_rotation: player_transform.rotation,
+        origin_forward: forward,
+        spawn_position: player_transform.translation - forward * 15.,
+      });
+    }
+    // A short-range burst of pellets fanned out in front of the player
+    SecondaryWeaponType::Shotgun => {
+      for i in 0..SHOTGUN_PELLETS {
+        let angle = -SHOTGUN_SPREAD / 2. + SHOTGUN_SPREAD * i as f32 / (SHOTGUN_PELLETS - 1) as f32;
+        let direction = Quat::from_rotation_z(angle) * forward;
+        projective_spawn_event.send(ProjectileSpawnEvent {
+          projectile_info: secondary_projectile_info(SHOTGUN_DAMAGE, 600., 0.2, Vec2::new(2., 2.), GREEN),
+          origin_rotation: player_transform.rotation * Quat::from_rotation_z(angle),
+          origin_forward: direction,
+          spawn_position: player_transform.translation + direction * 12.,
+        });
+      }
+    }
+  }
+}
+
+fn secondary_projectile_info(damage: u16, speed: f32, max_life_time: f32, size: Vec2, color: Color) -> ProjectileInfo {
+  ProjectileInfo {
+    damage,
+    speed,
+    max_life_time,
+    cooldown: 0.,
+    collider: Collider::cuboid(size.x / 2., size.y / 2.),
+    collision_groups: player_projectile_collision_groups(),
+    sprite: Sprite {
+      color,
+      custom_size: Some(size),
+      ..default()
+    },
+    by: EntityType::Player,
+    homing: None,
+    pierce: 0,
+    ricochet: 0,
+  }
+}
+
+// Speed and handling power ups are permanent until the loadout is reset, but can't be raised beyond a cap
+fn ship_stat_power_up_event(
+  mut power_up_collected_event: EventReader<PowerUpCollectedEvent>,
+  mut player_query: Query<&mut Player>,
+) {
+  for event in power_up_collected_event.read() {
+    for mut player in player_query.iter_mut() {
+      match event.power_up_type {
+        PowerUpType::Speed => {
+          player.movement_speed = (player.movement_speed + MOVEMENT_SPEED_PER_POWER_UP).min(MAX_MOVEMENT_SPEED);
+        }
+        PowerUpType::Handling => {
+          player.rotation_speed = (player.rotation_speed + ROTATION_SPEED_PER_POWER_UP).min(MAX_ROTATION_SPEED);
+        }
+        _ => continue,
+      }
+      info!(
+        "Power up collected: {:?} - movement speed {}, rotation speed {}",
+        event.power_up_type, player.movement_speed, player.rotation_speed
+      );
+    }
+  }
+}
+
+fn reset_ship_stats_event(
+  mut reset_loadout_event: EventReader<ResetLoadoutEvent>,
+  mut player_query: Query<&mut Player>,
+) {
+  for _ in reset_loadout_event.read() {
+    for mut player in player_query.iter_mut() {
+      info!("Resetting player movement and rotation speed");
+      player.movement_speed = MOVEMENT_SPEED;
+      player.rotation_speed = ROTATION_SPEED;
+    }
   }
 }
 
@@ -199,7 +470,7 @@ fn other_controls_system(
     info!("[F10] Upgrade player weapons");
     power_up_collected_event.send(PowerUpCollectedEvent {
       entity: Entity::from_raw(791234), // Will be despawned immediately when event is read
-      power_up_type: crate::shared::PowerUpType::Weapon,
+      power_up_type: PowerUpType::Weapon,
     });
   }
   if keyboard_input.just_pressed(KeyCode::F11) {
@@ -207,19 +478,3 @@ fn other_controls_system(
     reset_loadout_event.send(ResetLoadoutEvent {});
   }
 }
-
-fn player_wraparound_system(mut query: Query<&mut Transform, (With<RigidBody>, With<Player>)>) {
-  let extents = Vec3::new(WORLD_SIZE / 2., WORLD_SIZE / 2., 0.);
-  for mut transform in query.iter_mut() {
-    if transform.translation.x > extents.x {
-      transform.translation.x = -extents.x;
-    } else if transform.translation.x < -extents.x {
-      transform.translation.x = extents.x;
-    }
-    if transform.translation.y > extents.y {
-      transform.translation.y = -extents.y;
-    } else if transform.translation.y < -extents.y {
-      transform.translation.y = extents.y;
-    }
-  }
-}
@@ -0,0 +1,151 @@
+use crate::camera::PIXEL_PERFECT_BLOOM_LAYER;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::shared::{player_laser_collision_groups, PURPLE};
+use crate::shared_events::BeamDamageEvent;
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::pipeline::QueryFilter;
+use bevy_rapier2d::plugin::RapierContext;
+
+const LASER_KEY: KeyCode = KeyCode::KeyE;
+const RANGE: f32 = 250.;
+const NOSE_OFFSET: f32 = 12.;
+const DAMAGE_PER_SECOND: f32 = 20.;
+const DAMAGE_INTERVAL: f32 = 0.25; // Damage is dealt in ticks to avoid sending an event every frame
+const MAX_ENERGY: f32 = 100.;
+const DRAIN_PER_SECOND: f32 = 40.;
+const RECHARGE_PER_SECOND: f32 = 20.;
+const MIN_ENERGY_AFTER_DEPLETION: f32 = 30.; // Energy required before the laser can be fired again once depleted
+
+pub struct PlayerLaserPlugin;
+
+impl Plugin for PlayerLaserPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .add_systems(OnEnter(GameState::Dead), despawn_laser_beam_system)
+      .add_systems(Update, (equip_laser_system, render_laser_beam_system))
+      .add_systems(FixedUpdate, laser_system.run_if(in_state(GameState::Playing)));
+  }
+}
+
+#[derive(Component, Debug)]
+pub(crate) struct Laser {
+  pub energy: f32,
+  pub max_energy: f32,
+  is_depleted: bool,
+  is_firing: bool,
+  length: f32,
+  damage_cooldown: f32,
+}
+
+impl Default for Laser {
+  fn default() -> Self {
+    Laser {
+      energy: MAX_ENERGY,
+      max_energy: MAX_ENERGY,
+      is_depleted: false,
+      is_firing: false,
+      length: RANGE,
+      damage_cooldown: 0.,
+    }
+  }
+}
+
+#[derive(Component)]
+struct LaserBeam;
+
+fn equip_laser_system(mut commands: Commands, player_query: Query<Entity, Added<Player>>) {
+  for player in player_query.iter() {
+    commands
+      .entity(player)
+      .insert(Laser::default())
+      .with_children(|builder| {
+        builder.spawn((
+          ShapeBundle {
+            path: GeometryBuilder::build_as(&shapes::Line(Vec2::ZERO, Vec2::ZERO)),
+            spatial: SpatialBundle {
+              visibility: Visibility::Hidden,
+              ..default()
+            },
+            ..default()
+          },
+          Stroke::new(PURPLE, 2.),
+          Name::new("Laser Beam"),
+          LaserBeam,
+          PIXEL_PERFECT_BLOOM_LAYER,
+        ));
+      });
+  }
+}
+
+// Casts a ray from the ship's nose while the laser is fired and damages the first asteroid or enemy in its path
+fn laser_system(
+  time: Res<Time>,
+  keyboard_input: Res<ButtonInput<KeyCode>>,
+  rapier_context: Res<RapierContext>,
+  mut player_query: Query<(Entity, &Transform, &mut Laser), With<Player>>,
+  mut beam_damage_event: EventWriter<BeamDamageEvent>,
+) {
+  for (player, transform, mut laser) in player_query.iter_mut() {
+    let delta = time.delta_seconds();
+    if laser.is_depleted && laser.energy >= MIN_ENERGY_AFTER_DEPLETION {
+      laser.is_depleted = false;
+    }
+    laser.is_firing = keyboard_input.pressed(LASER_KEY) && !laser.is_depleted;
+    laser.damage_cooldown = (laser.damage_cooldown - delta).max(0.);
+    if !laser.is_firing {
+      laser.energy = (laser.energy + RECHARGE_PER_SECOND * delta).min(laser.max_energy);
+      continue;
+    }
+
+    laser.energy -= DRAIN_PER_SECOND * delta;
+    if laser.energy <= 0. {
+      laser.energy = 0.;
+      laser.is_depleted = true;
+    }
+    let direction = (transform.rotation * Vec3::Y).truncate();
+    let origin = transform.translation.truncate() + direction * NOSE_OFFSET;
+    let filter = QueryFilter::new()
+      .groups(player_laser_collision_groups())
+      .exclude_rigid_body(player)
+      .exclude_sensors();
+    laser.length = RANGE;
+    if let Some((target, distance)) = rapier_context.cast_ray(origin, direction, RANGE, true, filter) {
+      laser.length = distance;
+      if laser.damage_cooldown <= 0. {
+        laser.damage_cooldown = DAMAGE_INTERVAL;
+        beam_damage_event.send(BeamDamageEvent {
+          target,
+          damage: (DAMAGE_PER_SECOND * DAMAGE_INTERVAL).round() as u16,
+        });
+      }
+    }
+  }
+}
+
+fn render_laser_beam_system(
+  player_query: Query<&Laser, With<Player>>,
+  mut beam_query: Query<(&mut Path, &mut Visibility), With<LaserBeam>>,
+) {
+  for laser in player_query.iter() {
+    for (mut path, mut visibility) in beam_query.iter_mut() {
+      if laser.is_firing {
+        *path = GeometryBuilder::build_as(&shapes::Line(
+          Vec2::new(0., NOSE_OFFSET),
+          Vec2::new(0., NOSE_OFFSET + laser.length),
+        ));
+        *visibility = Visibility::Inherited;
+      } else {
+        *visibility = Visibility::Hidden;
+      }
+    }
+  }
+}
+
+fn despawn_laser_beam_system(mut commands: Commands, beam_query: Query<Entity, With<LaserBeam>>) {
+  for entity in beam_query.iter() {
+    commands.entity(entity).despawn();
+  }
+}
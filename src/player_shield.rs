@@ -1,4 +1,5 @@
 use crate::camera::PIXEL_PERFECT_BLOOM_LAYER;
+use crate::difficulty::Difficulty;
 use crate::game_state::GameState;
 use crate::player::Player;
 use crate::shared::{get_player_collision_groups, Category, ImpactInfo, PowerUpType, Shield, Substance, BLUE};
@@ -42,7 +43,9 @@ fn spawn_or_upgrade_shield_event(
   mut meshes: ResMut<Assets<Mesh>>,
   mut materials: ResMut<Assets<ColorMaterial>>,
   mut explosion_event: EventWriter<ExplosionEvent>,
+  difficulty: Res<Difficulty>,
 ) {
+  let strength = ((DEFAULT_MAX_STRENGTH as f32 * difficulty.shield_strength()).round() as i16).max(1);
   for event in power_up_collected_event.read() {
     if let Ok((player, transform)) = player_query.get_single() {
       if event.power_up_type != PowerUpType::Shield {
@@ -50,10 +53,10 @@ fn spawn_or_upgrade_shield_event(
       }
       info!("Power up collected: {:?}", event.power_up_type);
       let category = if (existing_shield_query.iter().count() as i16) > 0 {
-        upgrade_existing_shield(&mut existing_shield_query);
+        upgrade_existing_shield(&mut existing_shield_query, strength);
         Category::S
       } else {
-        spawn_shield(&mut commands, &mut meshes, &mut materials, &player);
+        spawn_shield(&mut commands, &mut meshes, &mut materials, &player, strength);
         commands.entity(player).remove::<Collider>();
         Category::L
       };
@@ -71,6 +74,7 @@ fn spawn_shield(
   meshes: &mut ResMut<Assets<Mesh>>,
   materials: &mut ResMut<Assets<ColorMaterial>>,
   player: &Entity,
+  strength: i16,
 ) {
   commands.entity(*player).with_children(|builder| {
     builder.spawn((
@@ -91,18 +95,18 @@ fn spawn_shield(
       Name::new("Shield"),
       Shield,
       ShieldInfo {
-        strength: DEFAULT_MAX_STRENGTH,
-        max_strength: DEFAULT_MAX_STRENGTH,
+        strength,
+        max_strength: strength,
       },
       PIXEL_PERFECT_BLOOM_LAYER,
     ));
   });
 }
 
-fn upgrade_existing_shield(existing_shield_query: &mut Query<&mut ShieldInfo>) {
+fn upgrade_existing_shield(existing_shield_query: &mut Query<&mut ShieldInfo>, strength: i16) {
   for mut shield in existing_shield_query.iter_mut() {
-    if shield.strength + DEFAULT_MAX_STRENGTH > shield.max_strength {
-      shield.max_strength += DEFAULT_MAX_STRENGTH;
+    if shield.strength + strength > shield.max_strength {
+      shield.max_strength += strength;
     }
     shield.strength = shield.max_strength;
     info!("Existing shield upgraded: {:?}", shield);
@@ -1,175 +1,151 @@
 use crate::game_state::GameState;
 use crate::player::Player;
-use crate::shared::{Category, PowerUpType, Substance, Weapon, WeaponSystem};
+use crate::shared::{
+  Category, PowerUpType, PrimaryProjectile, SecondaryWeapon, SecondaryWeaponType, Substance, UpgradeTree, WeaponBranch,
+  WeaponSystem,
+};
 use crate::shared_events::{ExplosionEvent, PowerUpCollectedEvent, ResetLoadoutEvent};
+use crate::weapon_progression::{PlayerWeaponProgression, WeaponProgression};
 use bevy::app::App;
 use bevy::log::info;
 use bevy::prelude::*;
 
-const MAX_LEVEL: u8 = 6;
+const MAX_FOCUSED_LEVEL: u8 = 3;
+const MAX_RAPID_FIRE_LEVEL: u8 = 3;
+const FOCUSED_DAMAGE_PER_LEVEL: f32 = 0.5;
+const FOCUSED_SPEED_PER_LEVEL: f32 = 0.15;
+const FOCUSED_PIERCE_PER_LEVEL: u8 = 1;
+const RAPID_FIRE_COOLDOWN_FACTOR: f32 = 0.8; // Applied once per level
+const MAX_FIRE_RATE_LEVEL: u8 = 3;
+const FIRE_RATE_COOLDOWN_FACTOR: f32 = 0.85; // Applied once per fire rate power up
+pub(crate) const BRANCH_KEYS: [(KeyCode, WeaponBranch); 3] = [
+  (KeyCode::Digit1, WeaponBranch::Spread),
+  (KeyCode::Digit2, WeaponBranch::Focused),
+  (KeyCode::Digit3, WeaponBranch::RapidFire),
+];
 
 pub struct PlayerWeaponPlugin;
 
 impl Plugin for PlayerWeaponPlugin {
   fn build(&self, app: &mut App) {
-    app.add_systems(
+    app.add_systems(Update, apply_weapon_progression_system).add_systems(
       Update,
-      (upgrade_weapon_event, reset_weapon_upgrades_event).run_if(in_state(GameState::Playing)),
+      (
+        upgrade_weapon_event,
+        choose_weapon_upgrade_system,
+        secondary_weapon_event,
+        fire_rate_power_up_event,
+        reset_weapon_upgrades_event,
+      )
+        .run_if(in_state(GameState::Playing)),
     );
   }
 }
 
 impl WeaponSystem {
-  pub fn reset(&mut self) -> &mut Self {
-    self.level = 1;
-    self.primary = vec![Weapon {
-      origin_offset: Vec3::new(0., 5., 0.),
-      direction: Vec3::Y,
-    }];
+  pub fn reset(&mut self, progression: &WeaponProgression) -> &mut Self {
+    self.upgrades = UpgradeTree::default();
+    self.fire_rate_level = 0;
+    self.apply(progression);
+    self.secondary = Some(SecondaryWeapon::new(SecondaryWeaponType::Missiles));
     self
   }
 
-  pub fn upgrade(&mut self) -> &mut Self {
-    self.level = (self.level + 1).min(MAX_LEVEL);
+  // Refills the ammo if the player already has this secondary weapon, otherwise replaces the current one
+  pub fn equip_secondary(&mut self, weapon_type: SecondaryWeaponType) -> &mut Self {
+    match self.secondary.as_mut() {
+      Some(secondary) if secondary.weapon_type == weapon_type => secondary.ammo = weapon_type.max_ammo(),
+      _ => self.secondary = Some(SecondaryWeapon::new(weapon_type)),
+    }
+    self
+  }
 
-    match self.level {
-      2 => self.level_2(),
-      3 => self.level_3(),
-      4 => self.level_4(),
-      5 => self.level_5(),
-      6 => self.level_6(),
-      _ => self.level_6(),
+  // The spread branch walks through the levels of the weapon progression, the other branches modify its projectiles
+  pub fn max_branch_level(branch: WeaponBranch, progression: &WeaponProgression) -> u8 {
+    match branch {
+      WeaponBranch::Spread => progression.max_level().saturating_sub(1),
+      WeaponBranch::Focused => MAX_FOCUSED_LEVEL,
+      WeaponBranch::RapidFire => MAX_RAPID_FIRE_LEVEL,
     }
   }
 
-  fn level_2(&mut self) -> &mut Self {
-    self.primary = vec![
-      Weapon {
-        origin_offset: Vec3::new(5., 5., 0.),
-        direction: Vec3::Y,
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., 5., 0.),
-        direction: Vec3::Y,
-      },
-    ];
-    self
+  pub fn can_upgrade(&self, branch: WeaponBranch, progression: &WeaponProgression) -> bool {
+    self.upgrades.level(branch) < Self::max_branch_level(branch, progression)
   }
 
-  fn level_3(&mut self) -> &mut Self {
-    self.primary = vec![
-      Weapon {
-        origin_offset: Vec3::new(5., 5., 0.),
-        direction: Vec3::new(0.4, 1., 0.).normalize(),
-      },
-      Weapon {
-        origin_offset: Vec3::new(0., 5., 0.),
-        direction: Vec3::Y,
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., 5., 0.),
-        direction: Vec3::new(-0.4, 1., 0.).normalize(),
-      },
-    ];
-    self
+  // The number of upgrades left across all branches, which limits how many weapon power ups can be pending
+  pub fn remaining_upgrades(&self, progression: &WeaponProgression) -> u8 {
+    WeaponBranch::ALL
+      .iter()
+      .map(|branch| Self::max_branch_level(*branch, progression).saturating_sub(self.upgrades.level(*branch)))
+      .sum()
   }
 
-  fn level_4(&mut self) -> &mut Self {
-    self.primary = vec![
-      Weapon {
-        origin_offset: Vec3::new(5., -3., 0.),
-        direction: Vec3::X,
-      },
-      Weapon {
-        origin_offset: Vec3::new(5., 5., 0.),
-        direction: Vec3::Y,
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., 5., 0.),
-        direction: Vec3::Y,
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., -3., 0.),
-        direction: Vec3::NEG_X,
-      },
-    ];
+  pub fn upgrade(&mut self, branch: WeaponBranch, progression: &WeaponProgression) -> &mut Self {
+    if !self.can_upgrade(branch, progression) {
+      return self;
+    }
+    match branch {
+      WeaponBranch::Spread => self.upgrades.spread += 1,
+      WeaponBranch::Focused => self.upgrades.focused += 1,
+      WeaponBranch::RapidFire => self.upgrades.rapid_fire += 1,
+    }
+    self.apply(progression);
     self
   }
 
-  fn level_5(&mut self) -> &mut Self {
-    self.primary = vec![
-      Weapon {
-        origin_offset: Vec3::new(0., 5., 0.),
-        direction: Vec3::Y,
-      },
-      Weapon {
-        origin_offset: Vec3::new(5., 5., 0.),
-        direction: Vec3::new(0.4, 1., 0.).normalize(),
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., 5., 0.),
-        direction: Vec3::new(-0.4, 1., 0.).normalize(),
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., 0., 0.),
-        direction: Vec3::NEG_X,
-      },
-      Weapon {
-        origin_offset: Vec3::new(5., 0., 0.),
-        direction: Vec3::X,
-      },
-    ];
+  pub fn raise_fire_rate(&mut self, progression: &WeaponProgression) -> &mut Self {
+    if self.fire_rate_level < MAX_FIRE_RATE_LEVEL {
+      self.fire_rate_level += 1;
+      self.apply(progression);
+    }
     self
   }
 
-  fn level_6(&mut self) -> &mut Self {
-    self.primary = vec![
-      Weapon {
-        origin_offset: Vec3::new(0., 5., 0.),
-        direction: Vec3::Y,
-      },
-      Weapon {
-        origin_offset: Vec3::new(5., 5., 0.),
-        direction: Vec3::new(0.4, 1., 0.).normalize(),
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., 5., 0.),
-        direction: Vec3::new(-0.4, 1., 0.).normalize(),
-      },
-      Weapon {
-        origin_offset: Vec3::new(5., -4., 0.),
-        direction: Vec3::X,
-      },
-      Weapon {
-        origin_offset: Vec3::new(-5., -4., 0.),
-        direction: Vec3::NEG_X,
-      },
-      Weapon {
-        origin_offset: Vec3::new(0., -5., 0.),
-        direction: Vec3::NEG_Y,
-      },
-    ];
-    self
+  // Only the spread branch moves through the levels of the progression, so it alone determines the level (and sprite)
+  fn apply(&mut self, progression: &WeaponProgression) {
+    let Some(level) = progression.level(1 + self.upgrades.spread) else {
+      return;
+    };
+    self.level = 1 + self.upgrades.spread;
+    let focused = self.upgrades.focused as f32;
+    self.primary = level.weapons.clone();
+    self.primary_projectile = PrimaryProjectile {
+      damage: (level.damage as f32 * (1. + focused * FOCUSED_DAMAGE_PER_LEVEL)).round() as u16,
+      cooldown: level.cooldown
+        * RAPID_FIRE_COOLDOWN_FACTOR.powi(self.upgrades.rapid_fire as i32)
+        * FIRE_RATE_COOLDOWN_FACTOR.powi(self.fire_rate_level as i32),
+      speed: level.speed * (1. + focused * FOCUSED_SPEED_PER_LEVEL),
+      max_life_time: level.max_life_time,
+      pierce: level.pierce + self.upgrades.focused * FOCUSED_PIERCE_PER_LEVEL,
+      ricochet: level.ricochet,
+    };
   }
 }
 
+// Collecting a weapon power up doesn't upgrade the weapon right away but lets the player choose a branch
 pub fn upgrade_weapon_event(
   mut power_up_collected_event: EventReader<PowerUpCollectedEvent>,
-  mut player_query: Query<(&Transform, &mut WeaponSystem, &mut Handle<Image>), With<Player>>,
+  mut player_query: Query<(&Transform, &mut WeaponSystem), With<Player>>,
   mut explosion_event: EventWriter<ExplosionEvent>,
-  asset_server: Res<AssetServer>,
+  progressions: Res<Assets<WeaponProgression>>,
+  progression: Res<PlayerWeaponProgression>,
 ) {
   for event in power_up_collected_event.read() {
-    for (transform, mut weapons, mut image_handle) in player_query.iter_mut() {
-      if event.power_up_type != PowerUpType::Weapon {
-        return;
+    if event.power_up_type != PowerUpType::Weapon {
+      continue;
+    }
+    let Some(progression) = progressions.get(&progression.0) else {
+      warn!("Weapon progression not loaded - weapon could not be upgraded");
+      continue;
+    };
+    for (transform, mut weapons) in player_query.iter_mut() {
+      if weapons.upgrades.pending < weapons.remaining_upgrades(progression) {
+        weapons.upgrades.pending += 1;
       }
-      let current_level = weapons.level;
-      weapons.upgrade();
-      update_player_sprite(&asset_server, &mut image_handle, weapons.level);
       info!(
-        "Power up collected: {:?} - upgraded weapon from level {} to {} and updated player sprite",
-        event.power_up_type, current_level, weapons.level
+        "Power up collected: {:?} - {} weapon upgrade(s) to choose",
+        event.power_up_type, weapons.upgrades.pending
       );
       explosion_event.send(ExplosionEvent {
         origin: transform.translation,
@@ -180,21 +156,243 @@ pub fn upgrade_weapon_event(
   }
 }
 
+fn choose_weapon_upgrade_system(
+  keyboard_input: Res<ButtonInput<KeyCode>>,
+  mut player_query: Query<(&mut WeaponSystem, &mut Handle<Image>), With<Player>>,
+  progressions: Res<Assets<WeaponProgression>>,
+  progression: Res<PlayerWeaponProgression>,
+) {
+  let Some(progression) = progressions.get(&progression.0) else {
+    return;
+  };
+  for (mut weapons, mut image_handle) in player_query.iter_mut() {
+    if weapons.upgrades.pending == 0 {
+      continue;
+    }
+    let Some(branch) = BRANCH_KEYS
+      .iter()
+      .find(|(key, _)| keyboard_input.just_pressed(*key))
+      .map(|(_, branch)| *branch)
+    else {
+      continue;
+    };
+    if !weapons.can_upgrade(branch, progression) {
+      continue;
+    }
+    weapons.upgrades.pending -= 1;
+    weapons.upgrade(branch, progression);
+    update_player_sprite(progression, &mut image_handle, weapons.level);
+    info!(
+      "Upgraded weapon: {} to level {}",
+      branch,
+      weapons.upgrades.level(branch)
+    );
+  }
+}
+
+fn secondary_weapon_event(
+  mut power_up_collected_event: EventReader<PowerUpCollectedEvent>,
+  mut player_query: Query<(&Transform, &mut WeaponSystem), With<Player>>,
+  mut explosion_event: EventWriter<ExplosionEvent>,
+) {
+  for event in power_up_collected_event.read() {
+    let PowerUpType::Secondary(weapon_type) = event.power_up_type else {
+      continue;
+    };
+    for (transform, mut weapons) in player_query.iter_mut() {
+      weapons.equip_secondary(weapon_type);
+      info!(
+        "Power up collected: {:?} - equipped secondary weapon",
+        event.power_up_type
+      );
+      explosion_event.send(ExplosionEvent {
+        origin: transform.translation,
+        category: Category::M,
+        substance: Substance::Energy,
+      });
+    }
+  }
+}
+
+fn fire_rate_power_up_event(
+  mut power_up_collected_event: EventReader<PowerUpCollectedEvent>,
+  mut player_query: Query<&mut WeaponSystem, With<Player>>,
+  progressions: Res<Assets<WeaponProgression>>,
+  progression: Res<PlayerWeaponProgression>,
+) {
+  for event in power_up_collected_event.read() {
+    if event.power_up_type != PowerUpType::FireRate {
+      continue;
+    }
+    let Some(progression) = progressions.get(&progression.0) else {
+      warn!("Weapon progression not loaded - fire rate could not be raised");
+      continue;
+    };
+    for mut weapons in player_query.iter_mut() {
+      weapons.raise_fire_rate(progression);
+      info!(
+        "Power up collected: {:?} - fire rate level {}, cooldown {:.3}s",
+        event.power_up_type, weapons.fire_rate_level, weapons.primary_projectile.cooldown
+      );
+    }
+  }
+}
+
 fn reset_weapon_upgrades_event(
   mut reset_loadout_event: EventReader<ResetLoadoutEvent>,
   mut weapon_query: Query<(&Player, &mut WeaponSystem, &mut Handle<Image>)>,
-  asset_server: Res<AssetServer>,
+  progressions: Res<Assets<WeaponProgression>>,
+  progression: Res<PlayerWeaponProgression>,
 ) {
   for _ in reset_loadout_event.read() {
+    let Some(progression) = progressions.get(&progression.0) else {
+      warn!("Weapon progression not loaded - weapon could not be reset");
+      continue;
+    };
     for (_, mut weapon_system, mut image_handle) in weapon_query.iter_mut() {
       info!("Resetting player weapon upgrades and sprite");
-      weapon_system.reset();
-      update_player_sprite(&asset_server, &mut image_handle, weapon_system.level);
+      weapon_system.reset(progression);
+      update_player_sprite(progression, &mut image_handle, weapon_system.level);
     }
   }
 }
 
-fn update_player_sprite(asset_server: &Res<AssetServer>, image_handle: &mut Handle<Image>, level: u8) {
-  let new_texture_path = format!("sprites/player_{}.png", level);
-  *image_handle = asset_server.load(new_texture_path);
+// Sets up the weapon system and sprite of a newly spawned player from the weapon progression, which is usually still
+// loading when the first player is spawned, and applies the progression again whenever it is (re)loaded
+fn apply_weapon_progression_system(
+  mut asset_event: EventReader<AssetEvent<WeaponProgression>>,
+  mut player_query: Query<(Ref<Player>, &mut WeaponSystem, &mut Handle<Image>)>,
+  progressions: Res<Assets<WeaponProgression>>,
+  progression: Res<PlayerWeaponProgression>,
+) {
+  let is_progression_loaded = asset_event
+    .read()
+    .any(|event| event.is_loaded_with_dependencies(&progression.0) || event.is_modified(&progression.0));
+  let Some(progression) = progressions.get(&progression.0) else {
+    return;
+  };
+  for (player, mut weapon_system, mut image_handle) in player_query.iter_mut() {
+    if is_progression_loaded || player.is_added() {
+      weapon_system.apply(progression);
+      update_player_sprite(progression, &mut image_handle, weapon_system.level);
+    }
+  }
+}
+
+fn update_player_sprite(progression: &WeaponProgression, image_handle: &mut Handle<Image>, level: u8) {
+  if let Some(level) = progression.level(level) {
+    *image_handle = level.sprite.clone();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shared::Weapon;
+  use crate::test_utils::assert_approx_eq;
+  use crate::weapon_progression::WeaponLevel;
+
+  fn progression(level_count: usize) -> WeaponProgression {
+    let levels = (1..=level_count)
+      .map(|muzzle_count| WeaponLevel {
+        sprite: Handle::default(),
+        damage: 4,
+        cooldown: 0.1,
+        speed: 100.,
+        max_life_time: 0.5,
+        pierce: 0,
+        ricochet: 0,
+        weapons: vec![
+          Weapon {
+            origin_offset: Vec3::ZERO,
+            direction: Vec3::Y,
+          };
+          muzzle_count
+        ],
+      })
+      .collect();
+    WeaponProgression { levels }
+  }
+
+  fn weapon_system(progression: &WeaponProgression) -> WeaponSystem {
+    let mut weapon_system = WeaponSystem::default();
+    weapon_system.reset(progression);
+    weapon_system
+  }
+
+  #[test]
+  fn spread_walks_through_the_levels_of_the_progression() {
+    let progression = progression(3);
+    let mut weapons = weapon_system(&progression);
+    assert_eq!((weapons.level, weapons.primary.len()), (1, 1));
+    weapons.upgrade(WeaponBranch::Spread, &progression);
+    assert_eq!((weapons.level, weapons.primary.len()), (2, 2));
+    weapons.upgrade(WeaponBranch::Spread, &progression);
+    assert_eq!((weapons.level, weapons.primary.len()), (3, 3));
+    assert!(!weapons.can_upgrade(WeaponBranch::Spread, &progression));
+  }
+
+  #[test]
+  fn other_branches_modify_projectiles_without_changing_the_level() {
+    let progression = progression(3);
+    let mut weapons = weapon_system(&progression);
+    weapons.upgrade(WeaponBranch::Focused, &progression);
+    weapons.upgrade(WeaponBranch::RapidFire, &progression);
+    assert_eq!((weapons.level, weapons.primary.len()), (1, 1));
+    assert_eq!(weapons.primary_projectile.damage, 6);
+    assert_eq!(weapons.primary_projectile.pierce, FOCUSED_PIERCE_PER_LEVEL);
+    assert_approx_eq(weapons.primary_projectile.speed, 115.);
+    assert_approx_eq(weapons.primary_projectile.cooldown, 0.1 * RAPID_FIRE_COOLDOWN_FACTOR);
+  }
+
+  #[test]
+  fn branches_cannot_be_upgraded_beyond_their_maximum() {
+    let progression = progression(3);
+    let mut weapons = weapon_system(&progression);
+    for _ in 0..10 {
+      weapons.upgrade(WeaponBranch::Focused, &progression);
+    }
+    assert_eq!(weapons.upgrades.focused, MAX_FOCUSED_LEVEL);
+    assert!(!weapons.can_upgrade(WeaponBranch::Focused, &progression));
+    assert!(weapons.can_upgrade(WeaponBranch::RapidFire, &progression));
+  }
+
+  #[test]
+  fn single_level_progression_cannot_be_spread() {
+    let progression = progression(1);
+    let mut weapons = weapon_system(&progression);
+    assert!(!weapons.can_upgrade(WeaponBranch::Spread, &progression));
+    weapons.upgrade(WeaponBranch::Spread, &progression);
+    assert_eq!(weapons.upgrades.spread, 0);
+  }
+
+  #[test]
+  fn remaining_upgrades_count_all_open_levels() {
+    let progression = progression(4);
+    let mut weapons = weapon_system(&progression);
+    assert_eq!(
+      weapons.remaining_upgrades(&progression),
+      3 + MAX_FOCUSED_LEVEL + MAX_RAPID_FIRE_LEVEL
+    );
+    weapons.upgrade(WeaponBranch::Spread, &progression);
+    weapons.upgrade(WeaponBranch::RapidFire, &progression);
+    assert_eq!(
+      weapons.remaining_upgrades(&progression),
+      2 + MAX_FOCUSED_LEVEL + MAX_RAPID_FIRE_LEVEL - 1
+    );
+  }
+
+  #[test]
+  fn reset_undoes_all_upgrades() {
+    let progression = progression(3);
+    let mut weapons = weapon_system(&progression);
+    weapons.upgrade(WeaponBranch::Spread, &progression);
+    weapons.upgrade(WeaponBranch::Focused, &progression);
+    weapons.raise_fire_rate(&progression);
+    weapons.reset(&progression);
+    assert_eq!(weapons.level, 1);
+    assert_eq!(weapons.upgrades, UpgradeTree::default());
+    assert_eq!(weapons.primary_projectile.damage, 4);
+    assert_approx_eq(weapons.primary_projectile.cooldown, 0.1);
+  }
 }
@@ -1,28 +1,40 @@
+use crate::difficulty::Difficulty;
 use crate::game_state::GameState;
 use crate::shared::{
-  power_up_collision_groups, random_game_world_point_away_from_player, Category, ImpactInfo, PowerUp, PowerUpType,
-  Substance,
+  power_up_collision_groups, random_game_world_point_away_from_player, random_u16_range, Category, ImpactInfo, PowerUp,
+  PowerUpType, SecondaryWeaponType, Substance, TimedEffect, WrapAroundEntity, BLUE, GREEN, ORANGE, PURPLE, RED, YELLOW,
+};
+use crate::shared_events::{
+  PowerUpChoiceEvent, PowerUpCollectedEvent, StaticIndicatorSpawnEvent, WaveClearedEvent, WaveEvent,
 };
-use crate::shared_events::{StaticIndicatorSpawnEvent, WaveEvent};
 use bevy::app::{App, Plugin};
 use bevy::asset::{AssetServer, Assets};
 use bevy::core::Name;
 use bevy::log::info;
 use bevy::math::UVec2;
 use bevy::prelude::{
-  default, Commands, Component, Deref, DerefMut, Entity, EventWriter, Handle, Image, OnEnter, Query, Res, ResMut,
-  SpriteBundle, TextureAtlas, TextureAtlasLayout, Time, Timer, TimerMode, Transform, Update, With,
+  default, in_state, Color, Commands, Component, Deref, DerefMut, Entity, EventReader, EventWriter, Handle, Image,
+  IntoSystemConfigs, OnEnter, Query, Res, ResMut, Sprite, SpriteBundle, TextureAtlas, TextureAtlasLayout, Time, Timer,
+  TimerMode, Transform, Update, With,
 };
 use bevy_rapier2d::dynamics::GravityScale;
 use bevy_rapier2d::geometry::Collider;
 
+const WAVE_REWARD_INTERVAL: f32 = 3.; // Waves between rewards on `Normal`
+const MIN_CHOICE_OPTIONS: u16 = 2;
+const MAX_CHOICE_OPTIONS: u16 = 3;
+
 pub struct PowerUpPlugin;
 
 impl Plugin for PowerUpPlugin {
   fn build(&self, app: &mut App) {
     app
       .add_systems(OnEnter(GameState::Starting), despawn_all_power_ups_system)
-      .add_systems(Update, animate_sprite_system);
+      .add_systems(Update, animate_sprite_system)
+      .add_systems(
+        Update,
+        (wave_reward_event, choice_power_up_collected_event).run_if(in_state(GameState::Playing)),
+      );
   }
 }
 
@@ -36,6 +48,8 @@ struct AnimationState {
 struct PowerUpInfo {
   power_up_type: PowerUpType,
   texture: Handle<Image>,
+  color: Color,
+  indicator_colour: Color,
   texture_atlas_layout: Handle<TextureAtlasLayout>,
   name: String,
   animation_state: AnimationState,
@@ -44,7 +58,6 @@ struct PowerUpInfo {
 #[derive(Component, Deref, DerefMut, Clone)]
 struct AnimationTimer(Timer);
 
-// TODO: Let player choose power up instead of dictating it
 pub(crate) fn spawn_power_ups(
   event: &WaveEvent,
   commands: &mut Commands,
@@ -72,6 +85,106 @@ pub(crate) fn spawn_power_ups(
       &mut static_indicator_spawn_event,
     );
   }
+  if let Some(secondary) = event.secondary_power_up {
+    spawn_power_up(
+      PowerUpType::Secondary(secondary),
+      event,
+      commands,
+      asset_server,
+      texture_atlas_layouts,
+      &mut static_indicator_spawn_event,
+    );
+  }
+  if let Some(stat) = &event.stat_power_up {
+    spawn_power_up(
+      stat.clone(),
+      event,
+      commands,
+      asset_server,
+      texture_atlas_layouts,
+      &mut static_indicator_spawn_event,
+    );
+  }
+  if let Some(effect) = event.timed_power_up {
+    spawn_power_up(
+      PowerUpType::Timed(effect),
+      event,
+      commands,
+      asset_server,
+      texture_atlas_layouts,
+      &mut static_indicator_spawn_event,
+    );
+  }
+  if event.choice_power_up {
+    spawn_power_up(
+      PowerUpType::Choice,
+      event,
+      commands,
+      asset_server,
+      texture_atlas_layouts,
+      &mut static_indicator_spawn_event,
+    );
+  }
+}
+
+pub(crate) fn random_secondary_weapon_type() -> SecondaryWeaponType {
+  let all = SecondaryWeaponType::ALL;
+  all[random_u16_range(0, all.len() as u16) as usize]
+}
+
+pub(crate) fn random_ship_stat_power_up_type() -> PowerUpType {
+  let all = PowerUpType::SHIP_STATS;
+  all[random_u16_range(0, all.len() as u16) as usize].clone()
+}
+
+pub(crate) fn random_timed_effect() -> TimedEffect {
+  let all = TimedEffect::ALL;
+  all[random_u16_range(0, all.len() as u16) as usize]
+}
+
+// Picks two or three different power ups for the player to choose from
+pub(crate) fn random_power_up_choice() -> Vec<PowerUpType> {
+  let mut pool = vec![
+    PowerUpType::Shield,
+    PowerUpType::Weapon,
+    PowerUpType::Secondary(random_secondary_weapon_type()),
+    random_ship_stat_power_up_type(),
+    PowerUpType::Timed(random_timed_effect()),
+  ];
+  let count = random_u16_range(MIN_CHOICE_OPTIONS, MAX_CHOICE_OPTIONS + 1).min(pool.len() as u16);
+  let mut options = vec![];
+  for _ in 0..count {
+    options.push(pool.remove(random_u16_range(0, pool.len() as u16) as usize));
+  }
+  options
+}
+
+// Every few cleared waves, the player gets to choose a power up as a reward
+fn wave_reward_event(
+  mut wave_cleared_events: EventReader<WaveClearedEvent>,
+  mut power_up_choice_event: EventWriter<PowerUpChoiceEvent>,
+  difficulty: Res<Difficulty>,
+) {
+  for event in wave_cleared_events.read() {
+    if difficulty.is_power_up_due(event.wave, WAVE_REWARD_INTERVAL) {
+      power_up_choice_event.send(PowerUpChoiceEvent {
+        options: random_power_up_choice(),
+      });
+    }
+  }
+}
+
+fn choice_power_up_collected_event(
+  mut power_up_collected_events: EventReader<PowerUpCollectedEvent>,
+  mut power_up_choice_event: EventWriter<PowerUpChoiceEvent>,
+) {
+  for event in power_up_collected_events.read() {
+    if event.power_up_type == PowerUpType::Choice {
+      power_up_choice_event.send(PowerUpChoiceEvent {
+        options: random_power_up_choice(),
+      });
+    }
+  }
 }
 
 fn spawn_power_up(
@@ -88,6 +201,10 @@ fn spawn_power_up(
     .spawn((
       SpriteBundle {
         texture: power_up_info.texture,
+        sprite: Sprite {
+          color: power_up_info.color,
+          ..default()
+        },
         transform: Transform::from_translation(spawn_point),
         ..default()
       },
@@ -108,11 +225,13 @@ fn spawn_power_up(
       },
       GravityScale(0.),
       power_up_collision_groups(),
+      WrapAroundEntity,
     ))
     .id();
   static_indicator_spawn_event.send(StaticIndicatorSpawnEvent {
     target_entity: power_up_entity,
     target_point: spawn_point,
+    colour: power_up_info.indicator_colour,
   });
   info!("Spawn: {:?} power up at {:?}", power_up_info.power_up_type, spawn_point);
 }
@@ -123,8 +242,33 @@ fn get_power_up_info(
   texture_atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
 ) -> PowerUpInfo {
   let texture = match power_up_type {
-    PowerUpType::Shield => asset_server.load("sprites/power_up_shield.png"),
-    PowerUpType::Weapon => asset_server.load("sprites/power_up_weapon.png"),
+    PowerUpType::Shield | PowerUpType::Timed(_) => asset_server.load("sprites/power_up_shield.png"),
+    PowerUpType::Weapon | PowerUpType::Secondary(_) | PowerUpType::Choice => {
+      asset_server.load("sprites/power_up_weapon.png")
+    }
+    PowerUpType::Speed => asset_server.load("sprites/power_up_speed.png"),
+    PowerUpType::Handling => asset_server.load("sprites/power_up_handling.png"),
+    PowerUpType::FireRate => asset_server.load("sprites/power_up_fire_rate.png"),
+  };
+  // Secondary weapons, the choice and timed effects share a sprite with others and are told apart by their tint
+  let color = match power_up_type {
+    PowerUpType::Choice => YELLOW,
+    PowerUpType::Timed(TimedEffect::RapidFire) => ORANGE,
+    PowerUpType::Timed(TimedEffect::Invulnerability) => BLUE,
+    PowerUpType::Timed(TimedEffect::DoubleScore) => YELLOW,
+    PowerUpType::Timed(TimedEffect::SlowMotion) => PURPLE,
+    PowerUpType::Secondary(SecondaryWeaponType::Missiles) => ORANGE,
+    PowerUpType::Secondary(SecondaryWeaponType::MineDropper) => RED,
+    PowerUpType::Secondary(SecondaryWeaponType::Shotgun) => GREEN,
+    _ => Color::WHITE,
+  };
+  // Ship stat power ups stand out from the others so that the player can tell them apart from a distance
+  let indicator_colour = match power_up_type {
+    PowerUpType::Speed => BLUE,
+    PowerUpType::Handling => PURPLE,
+    PowerUpType::FireRate => ORANGE,
+    PowerUpType::Timed(_) => YELLOW,
+    _ => GREEN,
   };
   let layout = TextureAtlasLayout::from_grid(UVec2::splat(32), 8, 1, None, None);
   let texture_atlas_layout = texture_atlas_layouts.add(layout);
@@ -133,6 +277,8 @@ fn get_power_up_info(
     name: format!("Power Up: {:?}", power_up_type.clone()),
     power_up_type,
     texture,
+    color,
+    indicator_colour,
     texture_atlas_layout,
     animation_state: AnimationState {
       timer: AnimationTimer(Timer::from_seconds(0.2, TimerMode::Repeating)),
@@ -4,9 +4,15 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::camera::PIXEL_PERFECT_BLOOM_LAYER;
+use crate::enemies::Enemy;
 use crate::game_state::GameState;
-use crate::shared::{Category, ImpactInfo, ProjectileInfo, Substance};
+use crate::player::Player;
+use crate::shared::{
+  is_friendly, Category, EntityType, HomingInfo, ImpactInfo, ProjectileInfo, Substance, WrapAroundEntity,
+};
 use crate::shared_events::ProjectileSpawnEvent;
+use crate::timed_power_ups::WorldTime;
+use crate::toroidal;
 
 pub struct ProjectilePlugin;
 
@@ -15,7 +21,7 @@ impl Plugin for ProjectilePlugin {
     app
       .add_systems(
         FixedUpdate,
-        process_projectile_spawn_event.run_if(in_state(GameState::Playing)),
+        (process_projectile_spawn_event, homing_system).run_if(in_state(GameState::Playing)),
       )
       .add_systems(Update, projectile_life_time_system);
   }
@@ -26,6 +32,16 @@ pub(crate) struct Projectile {
   pub damage: u16,
   pub life_time: f32,
   pub max_life_time: f32,
+  pub pierce: u8,   // Remaining number of targets the projectile can pass through
+  pub ricochet: u8, // Remaining number of times the projectile can bounce off asteroids
+}
+
+#[derive(Component)]
+struct Homing {
+  info: HomingInfo,
+  by: EntityType,
+  target: Option<Entity>,
+  fuel: f32,
 }
 
 // TODO: Fix bug where enemies die from asteroids
@@ -85,8 +101,11 @@ fn spawn_projectile(
         damage: projectile.damage,
         life_time: 0.,
         max_life_time: projectile.max_life_time,
+        pierce: projectile.pierce,
+        ricochet: projectile.ricochet,
       },
       PIXEL_PERFECT_BLOOM_LAYER,
+      WrapAroundEntity,
       AudioBundle {
         source: asset_server.load("audio/shoot_laser_default.ogg"),
         settings: PlaybackSettings {
@@ -98,6 +117,18 @@ fn spawn_projectile(
       },
     ))
     .id();
+  if projectile.pierce > 0 || projectile.ricochet > 0 {
+    // Sensors still report collisions but don't bounce off what they hit
+    commands.entity(entity).insert(Sensor);
+  }
+  if let Some(homing) = projectile.homing {
+    commands.entity(entity).insert(Homing {
+      info: homing,
+      by: projectile.by.clone(),
+      target: None,
+      fuel: homing.fuel,
+    });
+  }
   trace!(
     "Spawn: Projectile (id: {}, damage: {:?}, by: {:?})",
     entity,
@@ -106,12 +137,109 @@ fn spawn_projectile(
   );
 }
 
-fn projectile_life_time_system(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Projectile)>) {
-  for (entity, mut projectile) in query.iter_mut() {
-    projectile.life_time += time.delta_seconds();
+// Lets the projectile pass through whatever it hit, using up one of its remaining pierces
+pub(crate) fn pierce(mut entity: EntityWorldMut) {
+  if let Some(mut projectile) = entity.get_mut::<Projectile>() {
+    projectile.pierce = projectile.pierce.saturating_sub(1);
+  }
+}
+
+// Reflects the projectile off a surface with the given normal, using up one of its remaining ricochets
+pub(crate) fn ricochet(mut entity: EntityWorldMut, normal: Vec2) {
+  if let Some(mut projectile) = entity.get_mut::<Projectile>() {
+    projectile.ricochet = projectile.ricochet.saturating_sub(1);
+  }
+  let Some(mut velocity) = entity.get_mut::<Velocity>() else {
+    return;
+  };
+  let linvel = velocity.linvel;
+  if linvel.dot(normal) < 0. {
+    velocity.linvel = linvel - 2. * linvel.dot(normal) * normal;
+  }
+  if let Some(direction) = velocity.linvel.try_normalize() {
+    if let Some(mut transform) = entity.get_mut::<Transform>() {
+      transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction.extend(0.));
+    }
+  }
+}
+
+fn projectile_life_time_system(
+  mut commands: Commands,
+  time: Res<Time>,
+  world_time: Res<WorldTime>,
+  mut query: Query<(Entity, &mut Projectile, &CollisionGroups)>,
+) {
+  for (entity, mut projectile, collision_groups) in query.iter_mut() {
+    projectile.life_time += match is_friendly(collision_groups) {
+      true => time.delta_seconds(),
+      false => world_time.delta_seconds(),
+    };
 
     if projectile.life_time > projectile.max_life_time {
       commands.entity(entity).despawn();
     }
   }
 }
+
+// Homing projectiles acquire the nearest target of the opposing side within their acquisition cone and turn towards
+// it at a limited rate until they run out of fuel, after which they continue in a straight line
+fn homing_system(
+  time: Res<Time>,
+  world_time: Res<WorldTime>,
+  mut projectile_query: Query<(&mut Transform, &mut Velocity, &mut Homing), Without<Enemy>>,
+  enemy_query: Query<(Entity, &Transform), (With<Enemy>, Without<Player>, Without<Homing>)>,
+  player_query: Query<(Entity, &Transform), (With<Player>, Without<Enemy>, Without<Homing>)>,
+) {
+  for (mut transform, mut velocity, mut homing) in projectile_query.iter_mut() {
+    let forward = velocity.linvel.normalize_or_zero();
+    if homing.fuel <= 0. || forward == Vec2::ZERO {
+      continue;
+    }
+    // Runs in the fixed timestep, so the world clock is applied as a scale rather than through its own delta
+    let delta = match homing.by {
+      EntityType::Player => time.delta_seconds(),
+      _ => time.delta_seconds() * world_time.scale(),
+    };
+    homing.fuel -= delta;
+
+    let targets: Vec<(Entity, Vec3)> = match homing.by {
+      EntityType::Player => enemy_query.iter().map(|(e, t)| (e, t.translation)).collect(),
+      _ => player_query.iter().map(|(e, t)| (e, t.translation)).collect(),
+    };
+    let position = transform.translation;
+    let target = homing
+      .target
+      .and_then(|target| targets.iter().find(|(entity, _)| *entity == target).copied())
+      .or_else(|| acquire_target(&homing.info, position, forward, &targets));
+    homing.target = target.map(|(entity, _)| entity);
+
+    if let Some((_, target_position)) = target {
+      let desired = toroidal::direction(position, target_position).truncate();
+      let max_turn = homing.info.turn_rate * delta;
+      let angle = forward.angle_between(desired).clamp(-max_turn, max_turn);
+      let direction = Vec2::from_angle(angle).rotate(forward);
+      velocity.linvel = direction * velocity.linvel.length();
+      transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction.extend(0.));
+    }
+  }
+}
+
+fn acquire_target(
+  info: &HomingInfo,
+  position: Vec3,
+  forward: Vec2,
+  targets: &[(Entity, Vec3)],
+) -> Option<(Entity, Vec3)> {
+  targets
+    .iter()
+    .filter(|(_, target_position)| {
+      let delta = toroidal::shortest_delta(position, *target_position).truncate();
+      delta.length() <= info.acquisition_range && forward.angle_between(delta).abs() <= info.acquisition_cone
+    })
+    .min_by(|(_, a), (_, b)| {
+      toroidal::distance(position, *a)
+        .partial_cmp(&toroidal::distance(position, *b))
+        .unwrap_or(std::cmp::Ordering::Equal)
+    })
+    .copied()
+}
@@ -3,6 +3,7 @@ use std::fmt;
 use crate::asteroids::Asteroid;
 use crate::game_world::WORLD_SIZE;
 use crate::projectile::Projectile;
+use crate::toroidal;
 use bevy::color::Color;
 use bevy::prelude::*;
 use bevy_rapier2d::geometry::{Collider, Group};
@@ -41,6 +42,7 @@ pub(crate) const VERY_DARK_1: Color = Color::srgb(0.12, 0.14, 0.18);
 pub(crate) const VERY_DARK_2: Color = Color::srgb(0.06, 0.07, 0.09);
 
 pub(crate) const DEFAULT_FONT: &str = "fonts/bulkypix.ttf";
+const MAX_HEAT: f32 = 100.;
 
 #[derive(Component, Clone, Debug)]
 pub(crate) struct ProjectileInfo {
@@ -52,6 +54,17 @@ pub(crate) struct ProjectileInfo {
   pub collision_groups: CollisionGroups,
   pub sprite: Sprite,
   pub by: EntityType,
+  pub homing: Option<HomingInfo>,
+  pub pierce: u8,   // Number of targets the projectile passes through before being destroyed
+  pub ricochet: u8, // Number of times the projectile bounces off asteroids before being destroyed
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct HomingInfo {
+  pub turn_rate: f32,         // Radians per second
+  pub acquisition_cone: f32,  // Half angle in radians within which targets can be acquired
+  pub acquisition_range: f32, // Distance within which targets can be acquired
+  pub fuel: f32,              // Seconds after which the projectile stops steering and continues in a straight line
 }
 
 #[derive(Component, Clone, Debug)]
@@ -61,6 +74,7 @@ pub(crate) enum EntityType {
   Projectile,
   Asteroid,
   Enemy,
+  Mine,
   PowerUp,
   Unknown,
 }
@@ -72,6 +86,7 @@ pub(crate) enum CollisionEntityType {
   Projectile(Projectile),
   Asteroid(Asteroid),
   Enemy(CollisionDamage),
+  Mine,
   PowerUp(PowerUp),
   Unknown,
 }
@@ -84,6 +99,7 @@ impl From<CollisionEntityType> for EntityType {
       CollisionEntityType::Projectile(_) => EntityType::Projectile,
       CollisionEntityType::Asteroid(_) => EntityType::Asteroid,
       CollisionEntityType::Enemy(_) => EntityType::Enemy,
+      CollisionEntityType::Mine => EntityType::Mine,
       CollisionEntityType::PowerUp(_) => EntityType::PowerUp,
       CollisionEntityType::Unknown => EntityType::Unknown,
     }
@@ -95,23 +111,160 @@ pub(crate) struct WeaponSystem {
   pub level: u8,
   pub shooting_cooldown: f32,
   pub primary: Vec<Weapon>,
+  pub primary_projectile: PrimaryProjectile,
+  pub upgrades: UpgradeTree,
+  pub heat: Heat,
+  pub charge_time: f32,    // Seconds for which the fire button has been held down
+  pub fire_rate_level: u8, // Number of fire rate power ups collected
+  pub secondary: Option<SecondaryWeapon>,
+}
+
+// Rises with every shot of the primary weapon and locks it for a while once it reaches the maximum
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Heat {
+  pub value: f32,
+  pub max: f32,
+  pub lock_time: f32, // Remaining seconds during which the weapon can't be fired after overheating
+}
+
+impl Heat {
+  pub fn is_overheated(&self) -> bool {
+    self.lock_time > 0.
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WeaponBranch {
+  Spread,
+  Focused,
+  RapidFire,
+}
+
+impl WeaponBranch {
+  pub const ALL: [WeaponBranch; 3] = [WeaponBranch::Spread, WeaponBranch::Focused, WeaponBranch::RapidFire];
+}
+
+impl fmt::Display for WeaponBranch {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      WeaponBranch::Spread => write!(f, "Spread"),
+      WeaponBranch::Focused => write!(f, "Focused"),
+      WeaponBranch::RapidFire => write!(f, "Rapid fire"),
+    }
+  }
+}
+
+// The level of each weapon upgrade branch as well as the number of weapon power ups for which the player has yet to
+// choose a branch
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct UpgradeTree {
+  pub spread: u8,
+  pub focused: u8,
+  pub rapid_fire: u8,
+  pub pending: u8,
+}
+
+impl UpgradeTree {
+  pub fn level(&self, branch: WeaponBranch) -> u8 {
+    match branch {
+      WeaponBranch::Spread => self.spread,
+      WeaponBranch::Focused => self.focused,
+      WeaponBranch::RapidFire => self.rapid_fire,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PrimaryProjectile {
+  pub damage: u16,
+  pub cooldown: f32,
+  pub speed: f32,
+  pub max_life_time: f32,
+  pub pierce: u8,
+  pub ricochet: u8,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub(crate) struct Weapon {
   pub origin_offset: Vec3,
   pub direction: Vec3,
 }
 
-impl WeaponSystem {
-  pub fn new(cooldown: f32, y_offset: f32) -> Self {
+// The weapon system has no muzzles until the levels of a weapon progression are applied to it
+impl Default for WeaponSystem {
+  fn default() -> Self {
     WeaponSystem {
       level: 1,
-      shooting_cooldown: cooldown,
-      primary: vec![Weapon {
-        origin_offset: Vec3::new(0., y_offset, 0.),
-        direction: Vec3::Y,
-      }],
+      shooting_cooldown: 0.,
+      primary: vec![],
+      primary_projectile: PrimaryProjectile::default(),
+      upgrades: UpgradeTree::default(),
+      heat: Heat {
+        value: 0.,
+        max: MAX_HEAT,
+        lock_time: 0.,
+      },
+      charge_time: 0.,
+      fire_rate_level: 0,
+      secondary: Some(SecondaryWeapon::new(SecondaryWeaponType::Missiles)),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SecondaryWeaponType {
+  Missiles,
+  MineDropper,
+  Shotgun,
+}
+
+impl SecondaryWeaponType {
+  pub const ALL: [SecondaryWeaponType; 3] = [
+    SecondaryWeaponType::Missiles,
+    SecondaryWeaponType::MineDropper,
+    SecondaryWeaponType::Shotgun,
+  ];
+
+  pub fn max_ammo(&self) -> u16 {
+    match self {
+      SecondaryWeaponType::Missiles => 8,
+      SecondaryWeaponType::MineDropper => 5,
+      SecondaryWeaponType::Shotgun => 12,
+    }
+  }
+
+  pub fn cooldown(&self) -> f32 {
+    match self {
+      SecondaryWeaponType::Missiles => 1.2,
+      SecondaryWeaponType::MineDropper => 0.8,
+      SecondaryWeaponType::Shotgun => 0.6,
+    }
+  }
+}
+
+impl fmt::Display for SecondaryWeaponType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SecondaryWeaponType::Missiles => write!(f, "Missiles"),
+      SecondaryWeaponType::MineDropper => write!(f, "Mines"),
+      SecondaryWeaponType::Shotgun => write!(f, "Shotgun"),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SecondaryWeapon {
+  pub weapon_type: SecondaryWeaponType,
+  pub ammo: u16,
+  pub cooldown: f32,
+}
+
+impl SecondaryWeapon {
+  pub fn new(weapon_type: SecondaryWeaponType) -> Self {
+    SecondaryWeapon {
+      weapon_type,
+      ammo: weapon_type.max_ammo(),
+      cooldown: 0.,
     }
   }
 }
@@ -158,6 +311,75 @@ pub(crate) struct PowerUp {
 pub(crate) enum PowerUpType {
   Shield,
   Weapon,
+  Secondary(SecondaryWeaponType),
+  Choice,   // Lets the player choose one of several other power ups
+  Speed,    // Permanently raises the movement speed of the ship
+  Handling, // Permanently raises the rotation speed of the ship
+  FireRate, // Permanently lowers the cooldown of the primary weapon
+  Timed(TimedEffect),
+}
+
+impl PowerUpType {
+  pub const SHIP_STATS: [PowerUpType; 3] = [PowerUpType::Speed, PowerUpType::Handling, PowerUpType::FireRate];
+}
+
+impl fmt::Display for PowerUpType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PowerUpType::Shield => write!(f, "Shield"),
+      PowerUpType::Weapon => write!(f, "Weapon upgrade"),
+      PowerUpType::Secondary(weapon_type) => write!(f, "{}", weapon_type),
+      PowerUpType::Choice => write!(f, "Power up of your choice"),
+      PowerUpType::Speed => write!(f, "Speed"),
+      PowerUpType::Handling => write!(f, "Handling"),
+      PowerUpType::FireRate => write!(f, "Fire rate"),
+      PowerUpType::Timed(effect) => write!(f, "{}", effect),
+    }
+  }
+}
+
+// Power up effects which only last for a while, see `timed_power_ups`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TimedEffect {
+  RapidFire,
+  Invulnerability,
+  DoubleScore,
+  SlowMotion, // Slows down everything but the player and their projectiles
+}
+
+impl TimedEffect {
+  pub const ALL: [TimedEffect; 4] = [
+    TimedEffect::RapidFire,
+    TimedEffect::Invulnerability,
+    TimedEffect::DoubleScore,
+    TimedEffect::SlowMotion,
+  ];
+
+  // Seconds the effect lasts for when collected
+  pub fn duration(&self) -> f32 {
+    match self {
+      TimedEffect::RapidFire => 10.,
+      TimedEffect::Invulnerability => 6.,
+      TimedEffect::DoubleScore => 15.,
+      TimedEffect::SlowMotion => 8.,
+    }
+  }
+
+  // Collecting an effect that is already active extends it, but never beyond this
+  pub fn max_duration(&self) -> f32 {
+    self.duration() * 2.
+  }
+}
+
+impl fmt::Display for TimedEffect {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TimedEffect::RapidFire => write!(f, "Rapid fire"),
+      TimedEffect::Invulnerability => write!(f, "Invulnerability"),
+      TimedEffect::DoubleScore => write!(f, "Double score"),
+      TimedEffect::SlowMotion => write!(f, "Slow motion"),
+    }
+  }
 }
 
 #[derive(Component)]
@@ -196,7 +418,7 @@ pub(crate) fn random_game_world_point() -> Vec3 {
 
 pub(crate) fn random_game_world_point_away_from_player(player_position: Vec3, distance: f32) -> Vec3 {
   let proposed_point = random_game_world_point();
-  if (player_position - proposed_point).length() < distance {
+  if toroidal::distance(player_position, proposed_point) < distance {
     debug!(
       "Proposed spawn point {} too close to player {}, retrying...",
       proposed_point, player_position
@@ -218,7 +440,7 @@ pub(crate) fn random_game_world_point_close_to_origin_without_player_collision(
     origin.y + random::<f32>() * proximity,
     0.,
   );
-  if (player_position - proposed_point).length() < distance {
+  if toroidal::distance(player_position, proposed_point) < distance {
     debug!(
       "Proposed spawn point {} too close to player {}, retrying...",
       proposed_point, player_position
@@ -235,21 +457,30 @@ const POWER_UP_GROUP: Group = Group::GROUP_3;
 const ASTEROID_GROUP: Group = Group::GROUP_4;
 const ENEMY_GROUP: Group = Group::GROUP_5;
 const ENEMY_PROJECTILES_GROUP: Group = Group::GROUP_6;
+const MINE_GROUP: Group = Group::GROUP_7;
 
 pub(crate) fn get_player_collision_groups() -> CollisionGroups {
   CollisionGroups::new(
     PLAYER_GROUP,
-    POWER_UP_GROUP | ASTEROID_GROUP | ENEMY_GROUP | ENEMY_PROJECTILES_GROUP,
+    POWER_UP_GROUP | ASTEROID_GROUP | ENEMY_GROUP | ENEMY_PROJECTILES_GROUP | MINE_GROUP,
   )
 }
 
+pub(crate) fn is_friendly(collision_groups: &CollisionGroups) -> bool {
+  collision_groups.memberships == PLAYER_GROUP || collision_groups.memberships == FRIENDLY_PROJECTILES_GROUP
+}
+
 pub(crate) fn player_projectile_collision_groups() -> CollisionGroups {
   CollisionGroups::new(
     FRIENDLY_PROJECTILES_GROUP,
-    ASTEROID_GROUP | ENEMY_GROUP | ENEMY_PROJECTILES_GROUP,
+    ASTEROID_GROUP | ENEMY_GROUP | ENEMY_PROJECTILES_GROUP | MINE_GROUP,
   )
 }
 
+pub(crate) fn player_laser_collision_groups() -> CollisionGroups {
+  CollisionGroups::new(FRIENDLY_PROJECTILES_GROUP, ASTEROID_GROUP | ENEMY_GROUP | MINE_GROUP)
+}
+
 pub(crate) fn power_up_collision_groups() -> CollisionGroups {
   CollisionGroups::new(POWER_UP_GROUP, PLAYER_GROUP)
 }
@@ -261,6 +492,16 @@ pub(crate) fn default_enemy_collision_groups() -> CollisionGroups {
   )
 }
 
+// Drones fly in tight formations and therefore don't collide with other enemies
+pub(crate) fn swarm_drone_collision_groups() -> CollisionGroups {
+  CollisionGroups::new(ENEMY_GROUP, PLAYER_GROUP | FRIENDLY_PROJECTILES_GROUP | ASTEROID_GROUP)
+}
+
+// Mines are stationary and therefore ignore other enemies and their projectiles
+pub(crate) fn mine_collision_groups() -> CollisionGroups {
+  CollisionGroups::new(MINE_GROUP, PLAYER_GROUP | FRIENDLY_PROJECTILES_GROUP | ASTEROID_GROUP)
+}
+
 pub(crate) fn enemy_projectile_collision_groups() -> CollisionGroups {
   CollisionGroups::new(
     ENEMY_PROJECTILES_GROUP,
@@ -271,6 +512,6 @@ pub(crate) fn enemy_projectile_collision_groups() -> CollisionGroups {
 pub(crate) fn asteroid_collision_groups() -> CollisionGroups {
   CollisionGroups::new(
     ASTEROID_GROUP,
-    PLAYER_GROUP | FRIENDLY_PROJECTILES_GROUP | ASTEROID_GROUP | ENEMY_GROUP | ENEMY_PROJECTILES_GROUP,
+    PLAYER_GROUP | FRIENDLY_PROJECTILES_GROUP | ASTEROID_GROUP | ENEMY_GROUP | ENEMY_PROJECTILES_GROUP | MINE_GROUP,
   )
 }
@@ -1,7 +1,7 @@
-use crate::shared::{Category, EntityType, PowerUpType, ProjectileInfo, Substance};
+use crate::shared::{Category, EntityType, PowerUpType, ProjectileInfo, SecondaryWeaponType, Substance, TimedEffect};
 use bevy::app::{App, Plugin};
 use bevy::math::{Quat, Vec3};
-use bevy::prelude::{Entity, Event};
+use bevy::prelude::{Color, Entity, Event};
 
 pub struct SharedEventsPlugin;
 
@@ -10,6 +10,7 @@ impl Plugin for SharedEventsPlugin {
     app
       .add_event::<ScoreEvent>()
       .add_event::<WaveEvent>()
+      .add_event::<WaveClearedEvent>()
       .add_event::<NextWaveEvent>()
       .add_event::<ResetLoadoutEvent>()
       .add_event::<ExplosionEvent>()
@@ -18,7 +19,12 @@ impl Plugin for SharedEventsPlugin {
       .add_event::<AsteroidDestroyedEvent>()
       .add_event::<StaticIndicatorSpawnEvent>()
       .add_event::<PowerUpCollectedEvent>()
-      .add_event::<ShieldDamageEvent>();
+      .add_event::<PowerUpChoiceEvent>()
+      .add_event::<TimedEffectStartedEvent>()
+      .add_event::<TimedEffectExpiredEvent>()
+      .add_event::<ShieldDamageEvent>()
+      .add_event::<AreaDamageEvent>()
+      .add_event::<BeamDamageEvent>();
   }
 }
 
@@ -27,20 +33,67 @@ pub(crate) struct ScoreEvent {
   pub score: u16,
 }
 
-#[derive(Event, Debug)]
+#[derive(Event, Debug, Clone)]
 pub(crate) struct WaveEvent {
   pub player_position: Vec3,
   pub wave: u16,
   pub asteroid_count: u16,
   pub small_ufo_count: u16,
   pub large_ufo_count: u16,
+  pub swarm_count: u16,
+  pub mine_layer_count: u16,
   pub morph_boss: bool,
+  pub enemy_accuracy_modifier: f32,
   pub shield_power_up: bool,
   pub weapon_power_up: bool,
+  pub secondary_power_up: Option<SecondaryWeaponType>,
+  pub choice_power_up: bool,
+  pub stat_power_up: Option<PowerUpType>,
+  pub timed_power_up: Option<TimedEffect>,
+  pub reinforcements: Vec<SpawnGroup>,
 }
 
 /**
- * Despawns all asteroids and enemies, the former of which will trigger the next wave.
+ * Sent once a wave has been cleared, with a summary of that wave and a preview of the next one which will start after
+ * the countdown (in seconds) unless the player skips it.
+ */
+#[derive(Event, Debug)]
+pub(crate) struct WaveClearedEvent {
+  pub wave: u16,
+  pub clear_time: f32,
+  pub accuracy: f32,
+  pub score: u16,
+  pub next_wave: WaveEvent,
+  pub countdown: f32,
+}
+
+/**
+ * A group of enemies that joins a wave that is already in progress once its trigger condition is met.
+ */
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpawnGroup {
+  pub trigger: SpawnTrigger,
+  pub small_ufo_count: u16,
+  pub large_ufo_count: u16,
+  pub swarm_count: u16,
+  pub mine_layer_count: u16,
+  pub morph_boss: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SpawnTrigger {
+  AfterSeconds(f32),
+  AsteroidsBelow(usize),
+}
+
+impl Default for SpawnTrigger {
+  fn default() -> Self {
+    SpawnTrigger::AfterSeconds(0.)
+  }
+}
+
+/**
+ * Despawns all asteroids and enemies and discards any pending reinforcements, which will trigger the next wave.
  */
 #[derive(Event)]
 pub(crate) struct NextWaveEvent;
@@ -93,6 +146,7 @@ pub(crate) struct EnemyDamageEvent {
 pub(crate) struct StaticIndicatorSpawnEvent {
   pub target_entity: Entity,
   pub target_point: Vec3,
+  pub colour: Color,
 }
 
 #[derive(Event)]
@@ -101,7 +155,54 @@ pub(crate) struct PowerUpCollectedEvent {
   pub power_up_type: PowerUpType,
 }
 
+/**
+ * Sent to let the player choose one of the given power ups, e.g. as a reward for clearing a wave. The chosen one is
+ * applied by sending a `PowerUpCollectedEvent` for it.
+ */
+#[derive(Event, Debug)]
+pub(crate) struct PowerUpChoiceEvent {
+  pub options: Vec<PowerUpType>,
+}
+
+/**
+ * Sent when a timed effect becomes active. Collecting an effect that is already active only extends it, so this is not
+ * sent again until the effect has expired.
+ */
+#[derive(Event, Debug)]
+pub(crate) struct TimedEffectStartedEvent {
+  pub effect: TimedEffect,
+}
+
+/**
+ * Sent when a timed effect runs out, so that anything it changed can be reverted.
+ */
+#[derive(Event, Debug)]
+pub(crate) struct TimedEffectExpiredEvent {
+  pub effect: TimedEffect,
+}
+
 #[derive(Event)]
 pub(crate) struct ShieldDamageEvent {
   pub damage: u16,
 }
+
+/**
+ * Damages everything on the opposing side as well as asteroids within the radius of the origin e.g. when a mine
+ * detonates.
+ */
+#[derive(Event, Debug)]
+pub(crate) struct AreaDamageEvent {
+  pub origin: Vec3,
+  pub radius: f32,
+  pub damage: u16,
+  pub by: EntityType,
+}
+
+/**
+ * Damages a single asteroid or enemy that is hit by the player's laser beam, as if it had been hit by a projectile.
+ */
+#[derive(Event, Debug)]
+pub(crate) struct BeamDamageEvent {
+  pub target: Entity,
+  pub damage: u16,
+}
@@ -0,0 +1,11 @@
+use bevy::math::Vec3;
+
+// Assertions shared by the unit tests of all modules
+
+pub(crate) fn assert_approx_eq(actual: f32, expected: f32) {
+  assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+}
+
+pub(crate) fn assert_vec3_approx_eq(actual: Vec3, expected: Vec3) {
+  assert!(actual.abs_diff_eq(expected, 1e-4), "{:?} != {:?}", actual, expected);
+}
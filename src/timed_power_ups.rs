@@ -0,0 +1,227 @@
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::shared::{is_friendly, PowerUpType, TimedEffect};
+use crate::shared_events::{PowerUpCollectedEvent, TimedEffectExpiredEvent, TimedEffectStartedEvent};
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+use bevy_rapier2d::dynamics::Velocity;
+use bevy_rapier2d::geometry::CollisionGroups;
+use bevy_rapier2d::plugin::PhysicsSet;
+use std::time::Duration;
+
+const RAPID_FIRE_COOLDOWN_FACTOR: f32 = 0.5;
+const DOUBLE_SCORE_MULTIPLIER: f32 = 2.;
+const SLOW_MOTION_FACTOR: f32 = 0.4;
+const INVULNERABLE_ALPHA: f32 = 0.5;
+
+pub struct TimedPowerUpPlugin;
+
+impl Plugin for TimedPowerUpPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<TimedEffects>()
+      .init_resource::<WorldTime>()
+      .add_systems(OnEnter(GameState::Starting), reset_timed_effects_system)
+      .add_systems(PreUpdate, world_time_system)
+      .add_systems(
+        Update,
+        (timed_power_up_collected_event, timed_effects_system)
+          .chain()
+          .run_if(in_state(GameState::Playing)),
+      )
+      .add_systems(Update, (invulnerability_started_event, invulnerability_expired_event))
+      .add_systems(
+        PostUpdate,
+        (
+          slow_motion_system.before(PhysicsSet::SyncBackend),
+          restore_slow_motion_system.after(PhysicsSet::Writeback),
+        ),
+      );
+  }
+}
+
+// The timed effects that are currently active and the seconds they have left
+#[derive(Resource, Default, Debug)]
+pub(crate) struct TimedEffects {
+  pub active: Vec<(TimedEffect, f32)>,
+}
+
+impl TimedEffects {
+  pub fn is_active(&self, effect: TimedEffect) -> bool {
+    self.active.iter().any(|(active, _)| *active == effect)
+  }
+
+  pub fn cooldown_factor(&self) -> f32 {
+    if self.is_active(TimedEffect::RapidFire) {
+      RAPID_FIRE_COOLDOWN_FACTOR
+    } else {
+      1.
+    }
+  }
+
+  pub fn score_multiplier(&self) -> f32 {
+    if self.is_active(TimedEffect::DoubleScore) {
+      DOUBLE_SCORE_MULTIPLIER
+    } else {
+      1.
+    }
+  }
+}
+
+// The clock for everything the player is up against (enemies, their projectiles, mines, warp-ins and asteroids),
+// which runs slower than `Time` while slow motion is active - the player and their projectiles keep using `Time`
+#[derive(Resource, Debug)]
+pub(crate) struct WorldTime {
+  scale: f32,
+  delta: Duration,
+}
+
+impl Default for WorldTime {
+  fn default() -> Self {
+    Self {
+      scale: 1.,
+      delta: Duration::ZERO,
+    }
+  }
+}
+
+impl WorldTime {
+  pub fn scale(&self) -> f32 {
+    self.scale
+  }
+
+  pub fn delta(&self) -> Duration {
+    self.delta
+  }
+
+  pub fn delta_seconds(&self) -> f32 {
+    self.delta.as_secs_f32()
+  }
+}
+
+fn world_time_system(time: Res<Time>, timed_effects: Res<TimedEffects>, mut world_time: ResMut<WorldTime>) {
+  world_time.scale = if timed_effects.is_active(TimedEffect::SlowMotion) {
+    SLOW_MOTION_FACTOR
+  } else {
+    1.
+  };
+  world_time.delta = time.delta().mul_f32(world_time.scale);
+}
+
+// Different effects run side by side, while collecting one that is already active extends it (up to its maximum
+// duration) instead of starting it again
+fn timed_power_up_collected_event(
+  mut power_up_collected_event: EventReader<PowerUpCollectedEvent>,
+  mut timed_effects: ResMut<TimedEffects>,
+  mut timed_effect_started_event: EventWriter<TimedEffectStartedEvent>,
+) {
+  for event in power_up_collected_event.read() {
+    let PowerUpType::Timed(effect) = event.power_up_type else {
+      continue;
+    };
+    match timed_effects.active.iter_mut().find(|(active, _)| *active == effect) {
+      Some((_, remaining)) => {
+        *remaining = (*remaining + effect.duration()).min(effect.max_duration());
+        info!("Power up collected: {} extended to {:.0}s", effect, remaining);
+      }
+      None => {
+        timed_effects.active.push((effect, effect.duration()));
+        timed_effect_started_event.send(TimedEffectStartedEvent { effect });
+        info!("Power up collected: {} for {:.0}s", effect, effect.duration());
+      }
+    }
+  }
+}
+
+fn timed_effects_system(
+  time: Res<Time>,
+  mut timed_effects: ResMut<TimedEffects>,
+  mut timed_effect_expired_event: EventWriter<TimedEffectExpiredEvent>,
+) {
+  if timed_effects.active.is_empty() {
+    return;
+  }
+  for (_, remaining) in timed_effects.active.iter_mut() {
+    *remaining -= time.delta_seconds();
+  }
+  timed_effects.active.retain(|(effect, remaining)| {
+    if *remaining > 0. {
+      return true;
+    }
+    debug!("Timed effect expired: {}", effect);
+    timed_effect_expired_event.send(TimedEffectExpiredEvent { effect: *effect });
+    false
+  });
+}
+
+// Damage is ignored by the collision logic while the effect is active, this only lets the player know about it
+fn invulnerability_started_event(
+  mut timed_effect_started_event: EventReader<TimedEffectStartedEvent>,
+  mut player_query: Query<&mut Sprite, With<Player>>,
+) {
+  for event in timed_effect_started_event.read() {
+    if event.effect != TimedEffect::Invulnerability {
+      continue;
+    }
+    for mut sprite in player_query.iter_mut() {
+      sprite.color = sprite.color.with_alpha(INVULNERABLE_ALPHA);
+    }
+  }
+}
+
+fn invulnerability_expired_event(
+  mut timed_effect_expired_event: EventReader<TimedEffectExpiredEvent>,
+  mut player_query: Query<&mut Sprite, With<Player>>,
+) {
+  for event in timed_effect_expired_event.read() {
+    if event.effect != TimedEffect::Invulnerability {
+      continue;
+    }
+    for mut sprite in player_query.iter_mut() {
+      sprite.color = sprite.color.with_alpha(1.);
+    }
+  }
+}
+
+// Rapier integrates with `Time`, so the velocities of everything on the world clock are scaled by it for the physics
+// step and restored right after, which leaves the systems that steer them working with their actual velocities
+fn slow_motion_system(
+  world_time: Res<WorldTime>,
+  mut query: Query<(&mut Velocity, Option<&CollisionGroups>), Without<Player>>,
+) {
+  if world_time.scale() == 1. {
+    return;
+  }
+  for (mut velocity, collision_groups) in query.iter_mut() {
+    if !collision_groups.is_some_and(is_friendly) {
+      velocity.linvel *= world_time.scale();
+      velocity.angvel *= world_time.scale();
+    }
+  }
+}
+
+fn restore_slow_motion_system(
+  world_time: Res<WorldTime>,
+  mut query: Query<(&mut Velocity, Option<&CollisionGroups>), Without<Player>>,
+) {
+  if world_time.scale() == 1. {
+    return;
+  }
+  for (mut velocity, collision_groups) in query.iter_mut() {
+    if !collision_groups.is_some_and(is_friendly) {
+      velocity.linvel /= world_time.scale();
+      velocity.angvel /= world_time.scale();
+    }
+  }
+}
+
+fn reset_timed_effects_system(
+  mut timed_effects: ResMut<TimedEffects>,
+  mut world_time: ResMut<WorldTime>,
+  mut timed_effect_expired_event: EventWriter<TimedEffectExpiredEvent>,
+) {
+  for (effect, _) in timed_effects.active.drain(..) {
+    timed_effect_expired_event.send(TimedEffectExpiredEvent { effect });
+  }
+  *world_time = WorldTime::default();
+}
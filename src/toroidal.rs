@@ -0,0 +1,81 @@
+use crate::game_world::WORLD_SIZE;
+use bevy::math::Vec3;
+
+// Geometry on the wrapped game world: leaving the world on one side means entering it on the opposite side, so the
+// shortest path between two points may cross one or both edges of the world
+
+// The offset from `from` to `to` along the shortest path on the wrapped world
+pub(crate) fn shortest_delta(from: Vec3, to: Vec3) -> Vec3 {
+  let delta = to - from;
+  Vec3::new(wrap_offset(delta.x), wrap_offset(delta.y), delta.z)
+}
+
+// The length of the shortest path between two points on the wrapped world
+pub(crate) fn distance(from: Vec3, to: Vec3) -> f32 {
+  shortest_delta(from, to).truncate().length()
+}
+
+// The normalised direction of the shortest path from `from` to `to`, or zero if both points are the same
+pub(crate) fn direction(from: Vec3, to: Vec3) -> Vec3 {
+  shortest_delta(from, to).truncate().normalize_or_zero().extend(0.)
+}
+
+// The equivalent position inside the world's bounds
+pub(crate) fn wrap_position(position: Vec3) -> Vec3 {
+  Vec3::new(wrap_offset(position.x), wrap_offset(position.y), position.z)
+}
+
+fn wrap_offset(offset: f32) -> f32 {
+  (offset + WORLD_SIZE / 2.).rem_euclid(WORLD_SIZE) - WORLD_SIZE / 2.
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shortest_delta_within_the_world_is_the_plain_difference() {
+    let delta = shortest_delta(Vec3::new(-100., 50., 0.), Vec3::new(200., -50., 0.));
+    assert_eq!(delta, Vec3::new(300., -100., 0.));
+  }
+
+  #[test]
+  fn shortest_delta_crosses_the_world_edges() {
+    let delta = shortest_delta(Vec3::new(480., -490., 0.), Vec3::new(-480., 490., 0.));
+    assert_eq!(delta, Vec3::new(40., -20., 0.));
+  }
+
+  #[test]
+  fn shortest_delta_keeps_the_z_difference() {
+    let delta = shortest_delta(Vec3::new(0., 0., 1.), Vec3::new(0., 0., 5.));
+    assert_eq!(delta.z, 4.);
+  }
+
+  #[test]
+  fn distance_and_direction_use_the_shortest_path() {
+    let (from, to) = (Vec3::new(-490., 0., 0.), Vec3::new(490., 0., 0.));
+    assert_eq!(distance(from, to), 20.);
+    assert_eq!(direction(from, to), Vec3::new(-1., 0., 0.));
+    assert_eq!(direction(from, from), Vec3::ZERO);
+  }
+
+  #[test]
+  fn wrap_position_leaves_positions_inside_the_world_unchanged() {
+    let position = Vec3::new(-250., 499., 3.);
+    assert_eq!(wrap_position(position), position);
+  }
+
+  #[test]
+  fn wrap_position_moves_positions_outside_the_world_to_the_opposite_side() {
+    assert_eq!(wrap_position(Vec3::new(600., -700., 3.)), Vec3::new(-400., 300., 3.));
+    assert_eq!(
+      wrap_position(Vec3::new(WORLD_SIZE * 2. + 10., 0., 0.)),
+      Vec3::new(10., 0., 0.)
+    );
+  }
+
+  #[test]
+  fn wrap_position_maps_the_upper_edge_onto_the_lower_edge() {
+    assert_eq!(wrap_position(Vec3::new(500., 0., 0.)), Vec3::new(-500., 0., 0.));
+  }
+}
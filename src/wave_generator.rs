@@ -0,0 +1,374 @@
+use crate::shared::{random_f32_range, random_u16_range};
+use crate::shared_events::{SpawnGroup, SpawnTrigger};
+use bevy::app::{App, Plugin};
+use bevy::prelude::*;
+
+const MIN_COST: f32 = 0.1;
+
+pub struct WaveGeneratorPlugin;
+
+impl Plugin for WaveGeneratorPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .register_type::<WaveGeneratorConfig>()
+      .init_resource::<WaveGeneratorConfig>()
+      .add_systems(
+        Update,
+        validate_config_system.run_if(resource_changed::<WaveGeneratorConfig>),
+      );
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub(crate) enum Archetype {
+  Asteroid,
+  SmallUfo,
+  LargeUfo,
+  Swarm,
+  MineLayer,
+  MorphBoss,
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub(crate) struct ArchetypeCost {
+  pub archetype: Archetype,
+  pub cost: f32,
+  pub unlock_wave: u16,
+  pub max_per_wave: u16,
+}
+
+// Each wave receives a threat budget of `base_budget + budget_per_wave * (wave - 1) ^ budget_exponent` which is spent
+// on asteroids and enemies according to their cost - asteroids always receive a minimum share of the budget and a
+// share is held back for reinforcements that arrive during the wave
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct WaveGeneratorConfig {
+  pub base_budget: f32,
+  pub budget_per_wave: f32,
+  pub budget_exponent: f32,
+  pub asteroid_share: f32,
+  pub reinforcement_share: f32,
+  pub reinforcement_wave: u16,
+  pub boss_interval: u16,
+  pub costs: Vec<ArchetypeCost>,
+}
+
+impl Default for WaveGeneratorConfig {
+  fn default() -> Self {
+    WaveGeneratorConfig {
+      base_budget: 4.,
+      budget_per_wave: 3.,
+      budget_exponent: 1.15,
+      asteroid_share: 0.4,
+      reinforcement_share: 0.25,
+      reinforcement_wave: 2,
+      boss_interval: 4,
+      costs: vec![
+        ArchetypeCost {
+          archetype: Archetype::Asteroid,
+          cost: 1.,
+          unlock_wave: 1,
+          max_per_wave: u16::MAX,
+        },
+        ArchetypeCost {
+          archetype: Archetype::SmallUfo,
+          cost: 2.,
+          unlock_wave: 2,
+          max_per_wave: 8,
+        },
+        ArchetypeCost {
+          archetype: Archetype::LargeUfo,
+          cost: 5.,
+          unlock_wave: 3,
+          max_per_wave: 3,
+        },
+        ArchetypeCost {
+          archetype: Archetype::Swarm,
+          cost: 6.,
+          unlock_wave: 3,
+          max_per_wave: 2,
+        },
+        ArchetypeCost {
+          archetype: Archetype::MineLayer,
+          cost: 5.,
+          unlock_wave: 5,
+          max_per_wave: 2,
+        },
+        ArchetypeCost {
+          archetype: Archetype::MorphBoss,
+          cost: 10.,
+          unlock_wave: 4,
+          max_per_wave: 1,
+        },
+      ],
+    }
+  }
+}
+
+#[derive(Debug)]
+pub(crate) struct GeneratedWave {
+  pub asteroid_count: u16,
+  pub enemies: SpawnGroup,
+  pub reinforcements: Vec<SpawnGroup>,
+}
+
+impl WaveGeneratorConfig {
+  pub fn budget(&self, wave: u16) -> f32 {
+    self.base_budget + self.budget_per_wave * (wave.saturating_sub(1) as f32).powf(self.budget_exponent)
+  }
+
+  // Raises costs that are too low (or not a number) to the minimum, as spending the budget on an archetype that costs
+  // nothing would never end - returns whether any cost had to be changed
+  pub fn validate(&mut self) -> bool {
+    let mut is_changed = false;
+    for cost in self
+      .costs
+      .iter_mut()
+      .filter(|cost| cost.cost.is_nan() || cost.cost < MIN_COST)
+    {
+      warn!(
+        "Invalid cost of {} for {:?} raised to {}",
+        cost.cost, cost.archetype, MIN_COST
+      );
+      cost.cost = MIN_COST;
+      is_changed = true;
+    }
+    is_changed
+  }
+
+  fn cost_of(&self, archetype: Archetype) -> Option<&ArchetypeCost> {
+    self.costs.iter().find(|cost| cost.archetype == archetype)
+  }
+
+  pub fn generate(&self, wave: u16, budget_multiplier: f32) -> GeneratedWave {
+    let budget = self.budget(wave) * budget_multiplier;
+    let asteroid_cost = self
+      .cost_of(Archetype::Asteroid)
+      .map_or(1., |cost| cost.cost)
+      .max(MIN_COST);
+    let mut asteroid_count = ((budget * self.asteroid_share) / asteroid_cost).floor().max(1.) as u16;
+    let mut enemy_budget = budget - asteroid_count as f32 * asteroid_cost;
+
+    let mut reinforcement_budget = if wave >= self.reinforcement_wave {
+      enemy_budget * self.reinforcement_share
+    } else {
+      0.
+    };
+    enemy_budget -= reinforcement_budget;
+
+    // Bosses appear at a fixed interval once unlocked, as long as the budget can pay for them
+    let mut enemies = SpawnGroup::default();
+    if let Some(boss) = self.cost_of(Archetype::MorphBoss) {
+      if wave >= boss.unlock_wave
+        && self.boss_interval > 0
+        && wave % self.boss_interval == 0
+        && enemy_budget >= boss.cost
+      {
+        enemies.morph_boss = true;
+        enemy_budget -= boss.cost;
+      }
+    }
+    enemy_budget = self.spend(wave, enemy_budget, &mut enemies, None);
+
+    // The reinforcement budget is split between a group arriving after some time and one that arrives once most
+    // asteroids have been destroyed
+    let mut reinforcements = vec![];
+    if reinforcement_budget > 0. {
+      let mut timed = SpawnGroup {
+        trigger: SpawnTrigger::AfterSeconds(random_f32_range(20., 30.)),
+        ..default()
+      };
+      let mut conditional = SpawnGroup {
+        trigger: SpawnTrigger::AsteroidsBelow((asteroid_count / 2).max(1) as usize),
+        ..default()
+      };
+      let half = reinforcement_budget / 2.;
+      reinforcement_budget = self.spend(wave, half, &mut timed, Some(&enemies)) + reinforcement_budget - half;
+      reinforcement_budget = self.spend(wave, reinforcement_budget, &mut conditional, Some(&enemies));
+      reinforcements.extend([timed, conditional].into_iter().filter(|group| !is_empty(group)));
+    }
+
+    // Whatever couldn't be spent on enemies is spent on additional asteroids
+    asteroid_count += ((enemy_budget + reinforcement_budget) / asteroid_cost).floor() as u16;
+    GeneratedWave {
+      asteroid_count,
+      enemies,
+      reinforcements,
+    }
+  }
+
+  // Randomly picks affordable and unlocked enemies until the budget is exhausted and returns what's left of it
+  fn spend(&self, wave: u16, mut budget: f32, group: &mut SpawnGroup, other: Option<&SpawnGroup>) -> f32 {
+    loop {
+      let affordable: Vec<&ArchetypeCost> = self
+        .costs
+        .iter()
+        .filter(|cost| !matches!(cost.archetype, Archetype::Asteroid | Archetype::MorphBoss))
+        .filter(|cost| wave >= cost.unlock_wave && cost.cost <= budget)
+        .filter(|cost| count(group, cost.archetype) + other.map_or(0, |o| count(o, cost.archetype)) < cost.max_per_wave)
+        .collect();
+      if affordable.is_empty() {
+        return budget;
+      }
+      let choice = affordable[random_u16_range(0, affordable.len() as u16) as usize];
+      add(group, choice.archetype);
+      budget -= choice.cost;
+    }
+  }
+}
+
+// The config is a reflected resource that can be edited at runtime, so it is validated whenever it changes
+fn validate_config_system(mut config: ResMut<WaveGeneratorConfig>) {
+  if config.bypass_change_detection().validate() {
+    config.set_changed();
+  }
+}
+
+fn count(group: &SpawnGroup, archetype: Archetype) -> u16 {
+  match archetype {
+    Archetype::SmallUfo => group.small_ufo_count,
+    Archetype::LargeUfo => group.large_ufo_count,
+    Archetype::Swarm => group.swarm_count,
+    Archetype::MineLayer => group.mine_layer_count,
+    Archetype::MorphBoss => group.morph_boss as u16,
+    Archetype::Asteroid => 0,
+  }
+}
+
+fn add(group: &mut SpawnGroup, archetype: Archetype) {
+  match archetype {
+    Archetype::SmallUfo => group.small_ufo_count += 1,
+    Archetype::LargeUfo => group.large_ufo_count += 1,
+    Archetype::Swarm => group.swarm_count += 1,
+    Archetype::MineLayer => group.mine_layer_count += 1,
+    Archetype::MorphBoss => group.morph_boss = true,
+    Archetype::Asteroid => {}
+  }
+}
+
+fn is_empty(group: &SpawnGroup) -> bool {
+  group.small_ufo_count + group.large_ufo_count + group.swarm_count + group.mine_layer_count == 0 && !group.morph_boss
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn spent(config: &WaveGeneratorConfig, wave: &GeneratedWave) -> f32 {
+    let cost = |archetype: Archetype| config.cost_of(archetype).expect("Cost to exist").cost;
+    let group_cost = |group: &SpawnGroup| {
+      group.small_ufo_count as f32 * cost(Archetype::SmallUfo)
+        + group.large_ufo_count as f32 * cost(Archetype::LargeUfo)
+        + group.swarm_count as f32 * cost(Archetype::Swarm)
+        + group.mine_layer_count as f32 * cost(Archetype::MineLayer)
+        + if group.morph_boss {
+          cost(Archetype::MorphBoss)
+        } else {
+          0.
+        }
+    };
+    wave.asteroid_count as f32 * cost(Archetype::Asteroid)
+      + group_cost(&wave.enemies)
+      + wave.reinforcements.iter().map(group_cost).sum::<f32>()
+  }
+
+  #[test]
+  fn first_wave_only_has_asteroids() {
+    let wave = WaveGeneratorConfig::default().generate(1, 1.);
+    assert_eq!(wave.asteroid_count, 4);
+    assert!(is_empty(&wave.enemies));
+    assert!(wave.reinforcements.is_empty());
+  }
+
+  #[test]
+  fn budget_is_spent_without_being_exceeded() {
+    let config = WaveGeneratorConfig::default();
+    for wave_number in 1..=20 {
+      for budget_multiplier in [0.75, 1., 1.5] {
+        let wave = config.generate(wave_number, budget_multiplier);
+        let budget = config.budget(wave_number) * budget_multiplier;
+        let spent = spent(&config, &wave);
+        assert!(
+          spent <= budget + 1e-3,
+          "Wave {} spent {} of {}",
+          wave_number,
+          spent,
+          budget
+        );
+        assert!(
+          spent > budget - 1.,
+          "Wave {} spent {} of {}",
+          wave_number,
+          spent,
+          budget
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn locked_archetypes_are_not_spawned() {
+    let config = WaveGeneratorConfig::default();
+    for _ in 0..20 {
+      let wave = config.generate(2, 1.5);
+      for group in wave.reinforcements.iter().chain([&wave.enemies]) {
+        assert_eq!(group.large_ufo_count, 0);
+        assert_eq!(group.swarm_count, 0);
+        assert_eq!(group.mine_layer_count, 0);
+        assert!(!group.morph_boss);
+      }
+    }
+  }
+
+  #[test]
+  fn max_per_wave_is_respected() {
+    let config = WaveGeneratorConfig::default();
+    for _ in 0..20 {
+      let wave = config.generate(20, 3.);
+      for cost in config.costs.iter().filter(|cost| cost.archetype != Archetype::Asteroid) {
+        assert!(count(&wave.enemies, cost.archetype) <= cost.max_per_wave);
+        for reinforcement in &wave.reinforcements {
+          assert!(count(&wave.enemies, cost.archetype) + count(reinforcement, cost.archetype) <= cost.max_per_wave);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn boss_appears_at_its_interval_once_affordable() {
+    let config = WaveGeneratorConfig::default();
+    assert!(config.generate(8, 1.).enemies.morph_boss);
+    assert!(!config.generate(7, 1.).enemies.morph_boss);
+    assert!(!config.generate(4, 1.).enemies.morph_boss);
+  }
+
+  #[test]
+  fn reinforcements_only_arrive_from_the_reinforcement_wave() {
+    let config = WaveGeneratorConfig::default();
+    assert!(config.generate(1, 1.).reinforcements.is_empty());
+    assert!(!config.generate(10, 1.).reinforcements.is_empty());
+  }
+
+  #[test]
+  fn validate_raises_invalid_costs_to_the_minimum() {
+    let mut config = WaveGeneratorConfig::default();
+    assert!(!config.validate());
+    config.costs[1].cost = 0.;
+    config.costs[2].cost = -5.;
+    config.costs[3].cost = f32::NAN;
+    assert!(config.validate());
+    assert!(config.costs[1..=3].iter().all(|cost| cost.cost == MIN_COST));
+    assert!(!config.validate());
+  }
+
+  #[test]
+  fn validated_config_with_free_enemies_still_generates_a_wave() {
+    let mut config = WaveGeneratorConfig::default();
+    for cost in config.costs.iter_mut() {
+      cost.cost = 0.;
+    }
+    config.validate();
+    let wave = config.generate(10, 1.);
+    assert!(wave.enemies.small_ufo_count <= 8);
+  }
+}
@@ -3,31 +3,144 @@ use bevy::audio::Volume;
 use bevy::prelude::*;
 
 use crate::asteroids::Asteroid;
+use crate::difficulty::Difficulty;
+use crate::difficulty_director::DifficultyDirector;
+use crate::enemies::mine_layer::Mine;
+use crate::enemies::warp_in::WarpIn;
+use crate::enemies::Enemy;
 use crate::game_state::GameState;
 use crate::player::Player;
-use crate::shared_events::{AsteroidSpawnedEvent, StaticIndicatorSpawnEvent, WaveEvent};
-use crate::shared_resources::Wave;
+use crate::power_ups::{random_secondary_weapon_type, random_ship_stat_power_up_type, random_timed_effect};
+use crate::shared_events::{
+  AsteroidSpawnedEvent, NextWaveEvent, SpawnGroup, SpawnTrigger, StaticIndicatorSpawnEvent, WaveClearedEvent, WaveEvent,
+};
+use crate::shared_resources::{Score, Wave};
+use crate::wave_generator::WaveGeneratorConfig;
 
-const ASTEROID_START_COUNT: u16 = 1;
+const INTERMISSION_DURATION: f32 = 8.;
+const SKIP_INTERMISSION_KEY: KeyCode = KeyCode::Enter;
+const SHIELD_POWER_UP_INTERVAL: f32 = 2.; // Waves between power ups of each type on `Normal`
+const WEAPON_POWER_UP_INTERVAL: f32 = 3.;
+const SECONDARY_POWER_UP_INTERVAL: f32 = 4.;
+const CHOICE_POWER_UP_INTERVAL: f32 = 5.;
+const TIMED_POWER_UP_INTERVAL: f32 = 6.;
+const STAT_POWER_UP_INTERVAL: f32 = 7.;
 
 pub struct WavesPlugin;
 
 impl Plugin for WavesPlugin {
   fn build(&self, app: &mut App) {
     app
+      .init_resource::<WaveSchedule>()
       .add_systems(OnEnter(GameState::Starting), reset_waves_system)
-      .add_systems(FixedUpdate, start_next_wave.run_if(in_state(GameState::Playing)));
+      .add_systems(
+        FixedUpdate,
+        (reinforcements_system, start_next_wave, next_wave_event)
+          .chain()
+          .run_if(in_state(GameState::Playing)),
+      )
+      .add_systems(Update, intermission_system.run_if(in_state(GameState::Playing)));
   }
 }
 
+// The reinforcements of the current wave that haven't been spawned yet and the next wave, if it has been generated
+// but is still waiting for the intermission to end
+#[derive(Resource, Default)]
+pub(crate) struct WaveSchedule {
+  pub elapsed: f32,
+  pub pending: Vec<SpawnGroup>,
+  pub upcoming: Option<WaveEvent>,
+  pub intermission: Timer,
+  pub score_at_start: u16,
+}
+
+// Generates the next wave once the current one has been cleared and holds it back for a short intermission during
+// which the player can see a summary of the cleared wave and a preview of the next one
+fn start_next_wave(
+  asteroid_query: Query<Entity, With<Asteroid>>,
+  enemy_query: Query<Entity, (With<Enemy>, Without<Mine>)>,
+  warp_in_query: Query<Entity, With<WarpIn>>,
+  wave: Res<Wave>,
+  score: Res<Score>,
+  mut wave_schedule: ResMut<WaveSchedule>,
+  wave_generator_config: Res<WaveGeneratorConfig>,
+  mut director: ResMut<DifficultyDirector>,
+  difficulty: Res<Difficulty>,
+  mut wave_cleared_event: EventWriter<WaveClearedEvent>,
+) {
+  // A wave is complete once all asteroids and enemies (other than left over mines) have been destroyed and none are
+  // still to arrive
+  if wave_schedule.upcoming.is_some()
+    || !asteroid_query.is_empty()
+    || !enemy_query.is_empty()
+    || !warp_in_query.is_empty()
+    || !wave_schedule.pending.is_empty()
+  {
+    return;
+  }
+  if wave.0 > 0 {
+    director.evaluate_cleared_wave();
+  }
+  let next_wave = wave.0 + 1;
+  let generated_wave = wave_generator_config.generate(next_wave, director.budget_multiplier());
+  let event = WaveEvent {
+    player_position: Vec3::ZERO,
+    wave: next_wave,
+    asteroid_count: generated_wave.asteroid_count,
+    small_ufo_count: generated_wave.enemies.small_ufo_count,
+    large_ufo_count: generated_wave.enemies.large_ufo_count,
+    swarm_count: generated_wave.enemies.swarm_count,
+    mine_layer_count: generated_wave.enemies.mine_layer_count,
+    morph_boss: generated_wave.enemies.morph_boss,
+    enemy_accuracy_modifier: director.accuracy_modifier(),
+    shield_power_up: difficulty.is_power_up_due(next_wave, SHIELD_POWER_UP_INTERVAL),
+    weapon_power_up: difficulty.is_power_up_due(next_wave, WEAPON_POWER_UP_INTERVAL),
+    secondary_power_up: difficulty
+      .is_power_up_due(next_wave, SECONDARY_POWER_UP_INTERVAL)
+      .then(random_secondary_weapon_type),
+    choice_power_up: difficulty.is_power_up_due(next_wave, CHOICE_POWER_UP_INTERVAL),
+    stat_power_up: difficulty
+      .is_power_up_due(next_wave, STAT_POWER_UP_INTERVAL)
+      .then(random_ship_stat_power_up_type),
+    timed_power_up: difficulty
+      .is_power_up_due(next_wave, TIMED_POWER_UP_INTERVAL)
+      .then(random_timed_effect),
+    reinforcements: generated_wave.reinforcements,
+  };
+
+  // There's nothing to summarise before the first wave, so it starts right away
+  let intermission = if wave.0 > 0 { INTERMISSION_DURATION } else { 0. };
+  if wave.0 > 0 {
+    wave_cleared_event.send(WaveClearedEvent {
+      wave: wave.0,
+      clear_time: director.last_clear_time,
+      accuracy: director.last_accuracy,
+      score: score.0.saturating_sub(wave_schedule.score_at_start),
+      next_wave: event.clone(),
+      countdown: intermission,
+    });
+  }
+  debug!(
+    "Wave {} generated, starting in {}s: {:?}",
+    next_wave, intermission, event
+  );
+  wave_schedule.intermission = Timer::from_seconds(intermission, TimerMode::Once);
+  wave_schedule.upcoming = Some(event);
+}
+
 // TODO: Stop calling other functions directly from this system
 // This requires ensuring alternative is only executed once which is not the case when using EventWriter as, for
 // example, the AsteroidSpawnedEvent will be triggered again before the asteroid is spawned. Once this is fixed,
 // I can remove the vast majority of the parameters from this function.
-fn start_next_wave(
+fn intermission_system(
   mut commands: Commands,
-  asteroid_query: Query<Entity, With<Asteroid>>,
+  time: Res<Time>,
+  keyboard_input: Res<ButtonInput<KeyCode>>,
   mut wave: ResMut<Wave>,
+  score: Res<Score>,
+  mut wave_schedule: ResMut<WaveSchedule>,
+  mut director: ResMut<DifficultyDirector>,
+  difficulty: Res<Difficulty>,
   mut wave_event: EventWriter<WaveEvent>,
   asset_server: Res<AssetServer>,
   asteroid_spawn_event: EventWriter<AsteroidSpawnedEvent>,
@@ -35,21 +148,21 @@ fn start_next_wave(
   mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
   static_indicator_spawn_event: EventWriter<StaticIndicatorSpawnEvent>,
 ) {
-  if !asteroid_query.is_empty() {
+  if wave_schedule.upcoming.is_none() {
     return;
   }
-  wave.0 += 1;
-  let player_position = get_player_position(player_query);
-  let event = WaveEvent {
-    player_position,
-    wave: wave.0,
-    asteroid_count: wave.0 * 2 * ASTEROID_START_COUNT,
-    small_ufo_count: (wave.0 as f32 * 0.45).round() as u16,
-    large_ufo_count: if (wave.0 % 3) == 0 { 1u16 } else { 0u16 },
-    morph_boss: wave.0 % 4 == 0,
-    shield_power_up: (wave.0 + 1) % 2 == 0,
-    weapon_power_up: (wave.0 + 1) % 3 == 0,
+  wave_schedule.intermission.tick(time.delta());
+  let is_skipped = keyboard_input.just_pressed(SKIP_INTERMISSION_KEY);
+  if !wave_schedule.intermission.finished() && !is_skipped {
+    return;
+  }
+  let Some(mut event) = wave_schedule.upcoming.take() else {
+    return;
   };
+
+  wave.0 = event.wave;
+  event.player_position = get_player_position(player_query);
+  director.reset_wave_stats();
   info!("Starting wave {}: {:?}", wave.0, event);
   commands.spawn(AudioBundle {
     source: asset_server.load("audio/wave_started.ogg"),
@@ -60,9 +173,8 @@ fn start_next_wave(
     },
     ..Default::default()
   });
-  crate::asteroids::spawn_asteroid_wave(&event, &mut commands, asteroid_spawn_event);
-  crate::enemies::ufo::spawn_ufo_wave(&event, &mut commands, &asset_server);
-  crate::enemies::boss_morph::spawn_once(&event, &mut commands, &asset_server, &mut texture_atlas_layouts);
+  crate::asteroids::spawn_asteroid_wave(&event, &mut commands, asteroid_spawn_event, &difficulty);
+  spawn_enemies(&event, &mut commands, &asset_server, &difficulty);
   crate::power_ups::spawn_power_ups(
     &event,
     &mut commands,
@@ -70,9 +182,73 @@ fn start_next_wave(
     &mut texture_atlas_layouts,
     static_indicator_spawn_event,
   );
+  wave_schedule.elapsed = 0.;
+  wave_schedule.score_at_start = score.0;
+  wave_schedule.pending = event.reinforcements.clone();
   wave_event.send(event);
 }
 
+// Spawns the reinforcements of the current wave whose trigger condition is met - groups that are waiting for time to
+// pass are brought forward if there's nothing else left to fight so that the player isn't kept waiting
+fn reinforcements_system(
+  mut commands: Commands,
+  time: Res<Time>,
+  mut wave_schedule: ResMut<WaveSchedule>,
+  wave: Res<Wave>,
+  director: Res<DifficultyDirector>,
+  difficulty: Res<Difficulty>,
+  asteroid_query: Query<Entity, With<Asteroid>>,
+  enemy_query: Query<Entity, (With<Enemy>, Without<Mine>)>,
+  warp_in_query: Query<Entity, With<WarpIn>>,
+  player_query: Query<&Transform, With<Player>>,
+  asset_server: Res<AssetServer>,
+) {
+  if wave_schedule.pending.is_empty() {
+    return;
+  }
+  wave_schedule.elapsed += time.delta_seconds();
+  let elapsed = wave_schedule.elapsed;
+  let asteroid_count = asteroid_query.iter().count();
+  let is_field_clear = asteroid_count == 0 && enemy_query.is_empty() && warp_in_query.is_empty();
+  let (triggered, pending): (Vec<SpawnGroup>, Vec<SpawnGroup>) =
+    wave_schedule.pending.drain(..).partition(|group| match group.trigger {
+      SpawnTrigger::AfterSeconds(seconds) => elapsed >= seconds || is_field_clear,
+      SpawnTrigger::AsteroidsBelow(count) => asteroid_count < count,
+    });
+  wave_schedule.pending = pending;
+
+  let player_position = get_player_position(player_query);
+  for group in triggered {
+    info!("Reinforcements for wave {} arriving: {:?}", wave.0, group);
+    let event = WaveEvent {
+      player_position,
+      wave: wave.0,
+      asteroid_count: 0,
+      small_ufo_count: group.small_ufo_count,
+      large_ufo_count: group.large_ufo_count,
+      swarm_count: group.swarm_count,
+      mine_layer_count: group.mine_layer_count,
+      morph_boss: group.morph_boss,
+      enemy_accuracy_modifier: director.accuracy_modifier(),
+      shield_power_up: false,
+      weapon_power_up: false,
+      secondary_power_up: None,
+      choice_power_up: false,
+      stat_power_up: None,
+      timed_power_up: None,
+      reinforcements: vec![],
+    };
+    spawn_enemies(&event, &mut commands, &asset_server, &difficulty);
+  }
+}
+
+fn spawn_enemies(event: &WaveEvent, commands: &mut Commands, asset_server: &Res<AssetServer>, difficulty: &Difficulty) {
+  crate::enemies::ufo::spawn_ufo_wave(event, commands, asset_server, difficulty);
+  crate::enemies::swarm::spawn_swarm_wave(event, commands, asset_server);
+  crate::enemies::mine_layer::spawn_mine_layer_wave(event, commands, asset_server);
+  crate::enemies::boss_morph::spawn_once(event, commands, asset_server);
+}
+
 fn get_player_position(player_query: Query<&Transform, With<Player>>) -> Vec3 {
   let player_transform = player_query.get_single();
   if let Ok(player_transform) = player_transform {
@@ -81,6 +257,16 @@ fn get_player_position(player_query: Query<&Transform, With<Player>>) -> Vec3 {
   Vec3::ZERO
 }
 
-fn reset_waves_system(mut wave: ResMut<Wave>) {
+fn next_wave_event(mut next_wave_events: EventReader<NextWaveEvent>, mut wave_schedule: ResMut<WaveSchedule>) {
+  if next_wave_events.read().next().is_some() {
+    wave_schedule.pending.clear();
+  }
+}
+
+fn reset_waves_system(mut wave: ResMut<Wave>, mut wave_schedule: ResMut<WaveSchedule>) {
   wave.0 = 0;
+  wave_schedule.elapsed = 0.;
+  wave_schedule.pending.clear();
+  wave_schedule.upcoming = None;
+  wave_schedule.score_at_start = 0;
 }
@@ -0,0 +1,271 @@
+use crate::shared::Weapon;
+use bevy::app::{App, Plugin};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadState};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+const PLAYER_WEAPON_PROGRESSION: &str = "weapons/player.weapons.ron";
+
+pub struct WeaponProgressionPlugin;
+
+impl Plugin for WeaponProgressionPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_asset::<WeaponProgression>()
+      .init_asset_loader::<WeaponProgressionLoader>()
+      .add_systems(Startup, load_weapon_progression_system)
+      .add_systems(Update, report_weapon_progression_errors_system);
+  }
+}
+
+// The levels the player's primary weapon goes through when upgraded, loaded from an asset file so that new
+// progressions can be authored without recompiling
+#[derive(Asset, TypePath, Debug)]
+pub(crate) struct WeaponProgression {
+  pub levels: Vec<WeaponLevel>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct WeaponLevel {
+  pub sprite: Handle<Image>,
+  pub damage: u16,
+  pub cooldown: f32,
+  pub speed: f32,
+  pub max_life_time: f32,
+  pub pierce: u8,
+  pub ricochet: u8,
+  pub weapons: Vec<Weapon>,
+}
+
+impl WeaponProgression {
+  // Returns the given level (starting at 1), or the highest one if the progression doesn't go that far
+  pub fn level(&self, level: u8) -> Option<&WeaponLevel> {
+    self
+      .levels
+      .get((level.max(1) as usize - 1).min(self.levels.len().saturating_sub(1)))
+  }
+
+  pub fn max_level(&self) -> u8 {
+    self.levels.len().min(u8::MAX as usize) as u8
+  }
+}
+
+#[derive(Resource)]
+pub(crate) struct PlayerWeaponProgression(pub Handle<WeaponProgression>);
+
+// The file format of a weapon progression - see `assets/weapons/player.weapons.ron`
+#[derive(Deserialize)]
+struct WeaponProgressionDefinition {
+  levels: Vec<WeaponLevelDefinition>,
+}
+
+#[derive(Deserialize)]
+struct WeaponLevelDefinition {
+  sprite: String,
+  damage: u16,
+  cooldown: f32,
+  speed: f32,
+  max_life_time: f32,
+  #[serde(default)]
+  pierce: u8,
+  #[serde(default)]
+  ricochet: u8,
+  muzzles: Vec<MuzzleDefinition>,
+}
+
+#[derive(Deserialize)]
+struct MuzzleDefinition {
+  offset: (f32, f32),
+  direction: (f32, f32),
+}
+
+impl WeaponProgressionDefinition {
+  // Parses and validates everything but the sprites, which can only be checked once the file is being loaded as an asset
+  fn parse(bytes: &[u8]) -> Result<Self, WeaponProgressionError> {
+    let definition: Self = ron::de::from_bytes(bytes).map_err(WeaponProgressionError::Ron)?;
+    if definition.levels.is_empty() {
+      return Err(WeaponProgressionError::Invalid("no levels defined".to_string()));
+    }
+    for (i, level) in definition.levels.iter().enumerate() {
+      let invalid = |reason: &str| WeaponProgressionError::Invalid(format!("level {} {}", i + 1, reason));
+      if level.sprite.is_empty() {
+        return Err(invalid("has no sprite"));
+      }
+      if level.muzzles.is_empty() {
+        return Err(invalid("has no muzzles"));
+      }
+      if level.muzzles.iter().any(|muzzle| muzzle.direction == (0., 0.)) {
+        return Err(invalid("has a muzzle without direction"));
+      }
+    }
+    Ok(definition)
+  }
+}
+
+#[derive(Debug)]
+enum WeaponProgressionError {
+  Io(std::io::Error),
+  Ron(ron::de::SpannedError),
+  Invalid(String),
+}
+
+impl fmt::Display for WeaponProgressionError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      WeaponProgressionError::Io(error) => write!(f, "Could not read weapon progression: {}", error),
+      WeaponProgressionError::Ron(error) => write!(f, "Could not parse weapon progression: {}", error),
+      WeaponProgressionError::Invalid(reason) => write!(f, "Invalid weapon progression: {}", reason),
+    }
+  }
+}
+
+impl std::error::Error for WeaponProgressionError {}
+
+#[derive(Default)]
+struct WeaponProgressionLoader;
+
+impl AssetLoader for WeaponProgressionLoader {
+  type Asset = WeaponProgression;
+  type Settings = ();
+  type Error = WeaponProgressionError;
+
+  async fn load<'a>(
+    &'a self,
+    reader: &'a mut Reader<'_>,
+    _settings: &'a (),
+    load_context: &'a mut LoadContext<'_>,
+  ) -> Result<Self::Asset, Self::Error> {
+    let mut bytes = Vec::new();
+    reader
+      .read_to_end(&mut bytes)
+      .await
+      .map_err(WeaponProgressionError::Io)?;
+    let definition = WeaponProgressionDefinition::parse(&bytes)?;
+
+    let mut levels = vec![];
+    for (i, level) in definition.levels.into_iter().enumerate() {
+      load_context.read_asset_bytes(level.sprite.clone()).await.map_err(|_| {
+        WeaponProgressionError::Invalid(format!(
+          "level {} has a sprite that does not exist: {}",
+          i + 1,
+          level.sprite
+        ))
+      })?;
+      let weapons = level
+        .muzzles
+        .iter()
+        .map(|muzzle| Weapon {
+          origin_offset: Vec3::new(muzzle.offset.0, muzzle.offset.1, 0.),
+          direction: Vec3::new(muzzle.direction.0, muzzle.direction.1, 0.).normalize(),
+        })
+        .collect();
+      levels.push(WeaponLevel {
+        sprite: load_context.load(level.sprite),
+        damage: level.damage,
+        cooldown: level.cooldown,
+        speed: level.speed,
+        max_life_time: level.max_life_time,
+        pierce: level.pierce,
+        ricochet: level.ricochet,
+        weapons,
+      });
+    }
+    Ok(WeaponProgression { levels })
+  }
+
+  fn extensions(&self) -> &[&str] {
+    &["weapons.ron"]
+  }
+}
+
+fn load_weapon_progression_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+  commands.insert_resource(PlayerWeaponProgression(asset_server.load(PLAYER_WEAPON_PROGRESSION)));
+}
+
+// The loader returns an error if the file is invalid, which would otherwise go unnoticed because the weapons simply
+// never get upgraded
+fn report_weapon_progression_errors_system(
+  asset_server: Res<AssetServer>,
+  progression: Res<PlayerWeaponProgression>,
+  mut is_reported: Local<bool>,
+) {
+  if *is_reported {
+    return;
+  }
+  if let Some(LoadState::Failed(error)) = asset_server.get_load_state(&progression.0) {
+    error!("Failed to load {}: {}", PLAYER_WEAPON_PROGRESSION, error);
+    *is_reported = true;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const LEVEL: &str = r#"(sprite: "sprites/player_1.png", damage: 3, cooldown: 0.1, speed: 750.0, max_life_time: 0.4,
+    muzzles: [(offset: (0.0, 5.0), direction: (0.0, 1.0))])"#;
+
+  fn parse(levels: &[&str]) -> Result<WeaponProgressionDefinition, WeaponProgressionError> {
+    WeaponProgressionDefinition::parse(format!("(levels: [{}])", levels.join(",")).as_bytes())
+  }
+
+  fn assert_invalid(result: Result<WeaponProgressionDefinition, WeaponProgressionError>, expected: &str) {
+    match result {
+      Err(WeaponProgressionError::Invalid(reason)) => assert_eq!(reason, expected),
+      Err(error) => panic!("Expected invalid progression but got: {}", error),
+      Ok(_) => panic!("Expected invalid progression but it was accepted"),
+    }
+  }
+
+  #[test]
+  fn player_weapon_progression_is_valid() {
+    let definition = WeaponProgressionDefinition::parse(include_bytes!("../assets/weapons/player.weapons.ron"))
+      .expect("Player weapon progression to be valid");
+    assert!(!definition.levels.is_empty());
+  }
+
+  #[test]
+  fn pierce_and_ricochet_are_optional() {
+    let definition = parse(&[LEVEL]).expect("Level to be valid");
+    assert_eq!(definition.levels[0].pierce, 0);
+    assert_eq!(definition.levels[0].ricochet, 0);
+
+    let level = LEVEL.replace("max_life_time: 0.4,", "max_life_time: 0.4, pierce: 2, ricochet: 1,");
+    let definition = parse(&[&level]).expect("Level to be valid");
+    assert_eq!(definition.levels[0].pierce, 2);
+    assert_eq!(definition.levels[0].ricochet, 1);
+  }
+
+  #[test]
+  fn malformed_file_is_rejected() {
+    assert!(matches!(
+      WeaponProgressionDefinition::parse(b"(levels: [(sprite: 1)])"),
+      Err(WeaponProgressionError::Ron(_))
+    ));
+  }
+
+  #[test]
+  fn progression_without_levels_is_rejected() {
+    assert_invalid(parse(&[]), "no levels defined");
+  }
+
+  #[test]
+  fn level_without_sprite_is_rejected() {
+    let level = LEVEL.replace("sprites/player_1.png", "");
+    assert_invalid(parse(&[LEVEL, &level]), "level 2 has no sprite");
+  }
+
+  #[test]
+  fn level_without_muzzles_is_rejected() {
+    let level = LEVEL.replace("[(offset: (0.0, 5.0), direction: (0.0, 1.0))]", "[]");
+    assert_invalid(parse(&[&level]), "level 1 has no muzzles");
+  }
+
+  #[test]
+  fn muzzle_without_direction_is_rejected() {
+    let level = LEVEL.replace("direction: (0.0, 1.0)", "direction: (0.0, 0.0)");
+    assert_invalid(parse(&[LEVEL, LEVEL, &level]), "level 3 has a muzzle without direction");
+  }
+}